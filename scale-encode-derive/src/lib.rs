@@ -40,6 +40,10 @@ pub fn derive_macro(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
 }
 
 fn derive_with_attrs(attrs: TopLevelAttrs, input: DeriveInput) -> TokenStream2 {
+    if let Err(e) = check_field_attrs_parse(&input) {
+        return e.write_errors();
+    }
+
     // what type is the derive macro declared on?
     match &input.data {
         syn::Data::Enum(details) => generate_enum_impl(attrs, &input, details),
@@ -59,26 +63,92 @@ fn generate_enum_impl(
 ) -> TokenStream2 {
     let path_to_scale_encode = &attrs.crate_path;
     let path_to_type: syn::Path = input.ident.clone().into();
-    let (impl_generics, ty_generics, where_clause) = handle_generics(&attrs, &input.generics);
+    let fields = details.variants.iter().flat_map(|v| v.fields.iter());
+    let (impl_generics, ty_generics, where_clause) =
+        handle_generics(&attrs, &input.generics, fields);
+
+    // Discriminants can only be used to encode variants without fields, and must fit into
+    // the i128 that they are encoded from.
+    if attrs.discriminant {
+        if let Some(variant) = details.variants.iter().find(|v| !v.fields.is_empty()) {
+            return syn::Error::new_spanned(
+                variant,
+                "#[encode_as_type(discriminant)] can only be used on enums whose variants have no fields",
+            )
+            .into_compile_error();
+        }
+        let exprs = details
+            .variants
+            .iter()
+            .filter_map(|v| v.discriminant.as_ref());
+        for (_, expr) in exprs {
+            if let Err(e) = check_discriminant_fits(expr) {
+                return e.into_compile_error();
+            }
+        }
+    }
+    let discriminants = variant_discriminants(details);
+
+    for variant in &details.variants {
+        if let Err(e) = check_field_attrs(&variant.fields) {
+            return e.into_compile_error();
+        }
+    }
 
     // For each variant we want to spit out a match arm.
-    let match_arms = details.variants.iter().map(|variant| {
-        let variant_name = &variant.ident;
-        let variant_name_str = variant_name.to_string();
+    let match_arms = details
+        .variants
+        .iter()
+        .zip(discriminants)
+        .map(|(variant, discriminant)| {
+            let variant_name = &variant.ident;
+            let variant_name_str = variant_name.to_string();
 
-        let (matcher, composite) =
-            fields_to_matcher_and_composite(path_to_scale_encode, &variant.fields);
-        quote!(
-            Self::#variant_name #matcher => {
-                #path_to_scale_encode::Variant { name: #variant_name_str, fields: #composite }
-                    .encode_variant_as_type_to(
-                        __encode_as_type_type_id,
-                        __encode_as_type_types,
-                        __encode_as_type_out
-                    )
+            // Skipped variants can never be encoded, so return an error if we encounter one.
+            if should_skip(&variant.attrs) {
+                let err_str = format!("Variant {variant_name_str} is not encodable");
+                return quote!(
+                    Self::#variant_name { .. } => {
+                        Err(#path_to_scale_encode::Error::custom_str(#err_str))
+                    }
+                );
             }
-        )
-    });
+
+            // Variants are lined up with target variants by name, which can be overridden.
+            let target_name_str = field_attrs(&variant.attrs)
+                .rename
+                .unwrap_or(variant_name_str);
+
+            let (matcher, mut composite) =
+                fields_to_matcher_and_composite(path_to_scale_encode, &variant.fields);
+            let with_bindings = fields_to_with_bindings(path_to_scale_encode, &variant.fields);
+            if field_attrs(&variant.attrs).ignore_trailing_fields {
+                composite = quote!(#composite.ignore_trailing_fields());
+            }
+            let encode = if attrs.discriminant {
+                quote!(encode_variant_or_discriminant_as_type_to(
+                    #discriminant,
+                    __encode_as_type_type_id,
+                    __encode_as_type_types,
+                    __encode_as_type_out
+                ))
+            } else {
+                quote!(encode_variant_as_type_to(
+                    __encode_as_type_type_id,
+                    __encode_as_type_types,
+                    __encode_as_type_out
+                ))
+            };
+            quote!(
+                Self::#variant_name #matcher => {
+                    #with_bindings
+                    // bound so that the Variant is dropped before any `with` adapters it borrows:
+                    let __encode_as_type_res = #path_to_scale_encode::Variant { name: #target_name_str, fields: #composite }
+                        .#encode;
+                    __encode_as_type_res
+                }
+            )
+        });
 
     quote!(
         impl #impl_generics #path_to_scale_encode::EncodeAsType for #path_to_type #ty_generics #where_clause {
@@ -108,52 +178,232 @@ fn generate_struct_impl(
 ) -> TokenStream2 {
     let path_to_scale_encode = &attrs.crate_path;
     let path_to_type: syn::Path = input.ident.clone().into();
-    let (impl_generics, ty_generics, where_clause) = handle_generics(&attrs, &input.generics);
+    let (impl_generics, ty_generics, where_clause) =
+        handle_generics(&attrs, &input.generics, details.fields.iter());
+
+    if let Err(e) = check_field_attrs(&details.fields) {
+        return e.into_compile_error();
+    }
 
     let (matcher, composite) =
         fields_to_matcher_and_composite(path_to_scale_encode, &details.fields);
+    let with_bindings = fields_to_with_bindings(path_to_scale_encode, &details.fields);
 
-    quote!(
-        impl #impl_generics #path_to_scale_encode::EncodeAsType for #path_to_type #ty_generics #where_clause {
-            #[allow(unused_variables)]
-            fn encode_as_type_to<ScaleEncodeResolver: #path_to_scale_encode::TypeResolver>(
-                &self,
-                // long variable names to prevent conflict with struct field names:
-                __encode_as_type_type_id: ScaleEncodeResolver::TypeId,
-                __encode_as_type_types: &ScaleEncodeResolver,
-                __encode_as_type_out: &mut #path_to_scale_encode::Vec<u8>
-            ) -> Result<(), #path_to_scale_encode::Error> {
-                let #path_to_type #matcher = self;
+    // Users can opt out of the EncodeAsFields impl if they'd like to write their own.
+    let encode_as_fields_impl = if attrs.no_fields {
+        quote!()
+    } else {
+        quote!(
+            impl #impl_generics #path_to_scale_encode::EncodeAsFields for #path_to_type #ty_generics #where_clause {
+                #[allow(unused_variables)]
+                fn encode_as_fields_to<ScaleEncodeResolver: #path_to_scale_encode::TypeResolver>(
+                    &self,
+                    // long variable names to prevent conflict with struct field names:
+                    __encode_as_type_fields: &mut dyn #path_to_scale_encode::FieldIter<'_, ScaleEncodeResolver::TypeId>,
+                    __encode_as_type_types: &ScaleEncodeResolver,
+                    __encode_as_type_out: &mut #path_to_scale_encode::Vec<u8>
+                ) -> Result<(), #path_to_scale_encode::Error> {
+                    let #path_to_type #matcher = self;
+                    #with_bindings
+                    // bound so that the Composite is dropped before any `with` adapters it borrows:
+                    let __encode_as_type_res = #composite.encode_composite_fields_to(
+                        __encode_as_type_fields,
+                        __encode_as_type_types,
+                        __encode_as_type_out
+                    );
+                    __encode_as_type_res
+                }
+            }
+        )
+    };
+
+    // A #[repr(transparent)] struct is declared to have the same representation as its one
+    // non zero sized field, and so if that's the only field we encode and we can't encode the
+    // struct as a composite, we fall back to delegating to that field. The first attempt
+    // relies on seeing any error, so it mustn't be collected.
+    let encode_body = match transparent_field(input, details) {
+        Some(field) => quote!(
+            let #path_to_type #matcher = self;
+            let __encode_as_type_start = __encode_as_type_out.len();
+            let __encode_as_type_res = #path_to_scale_encode::error::suspend_error_collection(|| {
                 #composite.encode_composite_as_type_to(
-                    __encode_as_type_type_id,
+                    __encode_as_type_type_id.clone(),
                     __encode_as_type_types,
                     __encode_as_type_out
                 )
+            });
+            if __encode_as_type_res.is_ok() {
+                return Ok(());
             }
-        }
-        impl #impl_generics #path_to_scale_encode::EncodeAsFields for #path_to_type #ty_generics #where_clause {
+            __encode_as_type_out.truncate(__encode_as_type_start);
+            #path_to_scale_encode::EncodeAsType::encode_as_type_to(
+                #field,
+                __encode_as_type_type_id,
+                __encode_as_type_types,
+                __encode_as_type_out
+            )
+        ),
+        None => quote!(
+            let #path_to_type #matcher = self;
+            #with_bindings
+            // bound so that the Composite is dropped before any `with` adapters it borrows:
+            let __encode_as_type_res = #composite.encode_composite_as_type_to(
+                __encode_as_type_type_id,
+                __encode_as_type_types,
+                __encode_as_type_out
+            );
+            __encode_as_type_res
+        ),
+    };
+
+    // Fields encoded `with` some function are wrapped in adapters that only live as long as
+    // the encode call, so they can't be handed back from `flatten_fields`, and structs
+    // containing them can't be flattened.
+    let has_with_fields = details
+        .fields
+        .iter()
+        .any(|f| field_attrs(&f.attrs).with.is_some());
+    let flatten_fields_impl = if has_with_fields {
+        quote!()
+    } else {
+        let pushes = fields_to_pushes(path_to_scale_encode, &details.fields);
+        quote!(
+            impl #impl_generics #path_to_scale_encode::FlattenFields for #path_to_type #ty_generics #where_clause {
+                #[allow(unused_variables)]
+                fn flatten_fields<'__encode_as_type_a, ScaleEncodeResolver: #path_to_scale_encode::TypeResolver + '__encode_as_type_a>(
+                    &'__encode_as_type_a self,
+                    __encode_as_type_vals: &mut #path_to_scale_encode::Vec<(
+                        Option<&'__encode_as_type_a str>,
+                        #path_to_scale_encode::CompositeField<'__encode_as_type_a, ScaleEncodeResolver>
+                    )>
+                ) -> bool {
+                    let #path_to_type #matcher = self;
+                    #[allow(unused_mut)]
+                    let mut __encode_as_type_skipped = false;
+                    #( #pushes )*
+                    __encode_as_type_skipped
+                }
+            }
+        )
+    };
+
+    quote!(
+        impl #impl_generics #path_to_scale_encode::EncodeAsType for #path_to_type #ty_generics #where_clause {
             #[allow(unused_variables)]
-            fn encode_as_fields_to<ScaleEncodeResolver: #path_to_scale_encode::TypeResolver>(
+            fn encode_as_type_to<ScaleEncodeResolver: #path_to_scale_encode::TypeResolver>(
                 &self,
                 // long variable names to prevent conflict with struct field names:
-                __encode_as_type_fields: &mut dyn #path_to_scale_encode::FieldIter<'_, ScaleEncodeResolver::TypeId>,
+                __encode_as_type_type_id: ScaleEncodeResolver::TypeId,
                 __encode_as_type_types: &ScaleEncodeResolver,
                 __encode_as_type_out: &mut #path_to_scale_encode::Vec<u8>
             ) -> Result<(), #path_to_scale_encode::Error> {
-                let #path_to_type #matcher = self;
-                #composite.encode_composite_fields_to(
-                    __encode_as_type_fields,
-                    __encode_as_type_types,
-                    __encode_as_type_out
-                )
+                #encode_body
             }
         }
+        #encode_as_fields_impl
+        #flatten_fields_impl
     )
 }
 
-fn handle_generics<'a>(
+// Return an expression for the discriminant of each variant, as an i128 so that it can hold
+// any discriminant regardless of the enum's repr. Variants without an explicit discriminant
+// are one greater than the previous variant, starting from 0.
+fn variant_discriminants(details: &syn::DataEnum) -> Vec<TokenStream2> {
+    let mut last_explicit: Option<&syn::Expr> = None;
+    let mut offset = 0i128;
+    details
+        .variants
+        .iter()
+        .map(|variant| {
+            if let Some((_, expr)) = &variant.discriminant {
+                last_explicit = Some(expr);
+                offset = 0;
+            }
+            let discriminant = match last_explicit {
+                Some(expr) => quote!((#expr) as i128 + #offset),
+                None => quote!(#offset),
+            };
+            offset += 1;
+            discriminant
+        })
+        .collect()
+}
+
+// Check that a literal discriminant, possibly negated, fits into an i128. Other expressions
+// are left for the compiler to check when they're cast.
+fn check_discriminant_fits(expr: &syn::Expr) -> Result<(), syn::Error> {
+    let (lit, negated) = match expr {
+        syn::Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Int(lit),
+            ..
+        }) => (lit, false),
+        syn::Expr::Unary(syn::ExprUnary {
+            op: syn::UnOp::Neg(_),
+            expr,
+            ..
+        }) => match &**expr {
+            syn::Expr::Lit(syn::ExprLit {
+                lit: syn::Lit::Int(lit),
+                ..
+            }) => (lit, true),
+            _ => return Ok(()),
+        },
+        _ => return Ok(()),
+    };
+
+    let fits = match lit.base10_parse::<u128>() {
+        Ok(n) if negated => n <= i128::MIN.unsigned_abs(),
+        Ok(n) => n <= i128::MAX as u128,
+        Err(_) => false,
+    };
+    if fits {
+        Ok(())
+    } else {
+        Err(syn::Error::new_spanned(
+            expr,
+            "#[encode_as_type(discriminant)] only supports discriminants which fit into an i128",
+        ))
+    }
+}
+
+// If the struct is #[repr(transparent)] and has exactly one field that isn't skipped,
+// return the identifier that this field is bound to by the matcher.
+fn transparent_field(input: &DeriveInput, details: &syn::DataStruct) -> Option<syn::Ident> {
+    let is_transparent = input.attrs.iter().any(|attr| {
+        let mut is_transparent = false;
+        if attr.path().is_ident("repr") {
+            let _ = attr.parse_nested_meta(|meta| {
+                is_transparent |= meta.path.is_ident("transparent");
+                Ok(())
+            });
+        }
+        is_transparent
+    });
+    if !is_transparent {
+        return None;
+    }
+
+    let mut fields = details
+        .fields
+        .iter()
+        .enumerate()
+        .filter(|(_, f)| !should_skip_field(f));
+    let (idx, field) = fields.next()?;
+    let attrs = field_attrs(&field.attrs);
+    if fields.next().is_some() || attrs.skip_if.is_some() || attrs.with.is_some() {
+        return None;
+    }
+
+    Some(match &field.ident {
+        Some(ident) => ident.clone(),
+        None => format_ident!("_{idx}"),
+    })
+}
+
+fn handle_generics<'a, 'f>(
     attrs: &TopLevelAttrs,
     generics: &'a syn::Generics,
+    fields: impl Iterator<Item = &'f syn::Field>,
 ) -> (
     syn::ImplGenerics<'a>,
     syn::TypeGenerics<'a>,
@@ -168,64 +418,267 @@ fn handle_generics<'a>(
         // if custom trait bounds are given, append those to the where clause.
         where_clause.predicates.extend(where_predicates.clone());
     } else {
-        // else, append our default EncodeAsType bounds to the where clause.
+        // else, append any bounds given on fields, and default EncodeAsType bounds for
+        // any type parameters which aren't only used in fields with their own bounds (fields
+        // encoded `with` some function don't need to implement EncodeAsType either).
+        let mut fields_with_bounds = Vec::new();
+        let mut fields_without_bounds = Vec::new();
+        for field in fields {
+            let attrs = field_attrs(&field.attrs);
+            match attrs.bound {
+                Some(bound) => {
+                    where_clause.predicates.extend(bound);
+                    fields_with_bounds.push(&field.ty);
+                }
+                None if attrs.with.is_some() || is_phantom_data(&field.ty) => {
+                    fields_with_bounds.push(&field.ty)
+                }
+                None => fields_without_bounds.push(&field.ty),
+            }
+        }
+
         for param in generics.type_params() {
             let ty = &param.ident;
-            where_clause
-                .predicates
-                .push(syn::parse_quote!(#ty: #path_to_crate::EncodeAsType))
+            let is_only_in_bounded_fields = fields_with_bounds
+                .iter()
+                .any(|f| type_mentions(&quote!(#f), ty))
+                && !fields_without_bounds
+                    .iter()
+                    .any(|f| type_mentions(&quote!(#f), ty));
+            if !is_only_in_bounded_fields {
+                where_clause
+                    .predicates
+                    .push(syn::parse_quote!(#ty: #path_to_crate::EncodeAsType))
+            }
         }
     }
 
     (impl_generics, ty_generics, where_clause)
 }
 
+// Does the given type (as tokens) mention the given identifier anywhere?
+fn type_mentions(tokens: &TokenStream2, ident: &syn::Ident) -> bool {
+    tokens.clone().into_iter().any(|t| match t {
+        proc_macro2::TokenTree::Ident(i) => &i == ident,
+        proc_macro2::TokenTree::Group(g) => type_mentions(&g.stream(), ident),
+        _ => false,
+    })
+}
+
 fn fields_to_matcher_and_composite(
     path_to_scale_encode: &syn::Path,
     fields: &syn::Fields,
 ) -> (TokenStream2, TokenStream2) {
-    match fields {
+    let matcher = match fields {
         syn::Fields::Named(fields) => {
             let match_body = fields.named.iter().map(|f| {
                 let field_name = &f.ident;
                 quote!(#field_name)
             });
-            let tuple_body = fields.named
+            quote!({#( #match_body ),*})
+        }
+        syn::Fields::Unnamed(fields) => {
+            let match_body = (0..fields.unnamed.len()).map(|idx| format_ident!("_{idx}"));
+            quote!((#( #match_body ),*))
+        }
+        syn::Fields::Unit => quote!(),
+    };
+
+    // If any fields are flattened or conditionally skipped, we don't know up front how
+    // many values we'll end up with, and so we gather them into a Vec first.
+    let has_dynamic_fields = fields.iter().any(|f| {
+        let attrs = field_attrs(&f.attrs);
+        (attrs.flatten || attrs.skip_if.is_some()) && !should_skip_field(f)
+    });
+    if has_dynamic_fields {
+        let pushes = fields_to_pushes(path_to_scale_encode, fields);
+        // If any fields were skipped, the rest can no longer be lined up by position.
+        let composite = quote!({
+            #[allow(unused_mut)]
+            let mut __encode_as_type_skipped = false;
+            let mut __encode_as_type_vec = #path_to_scale_encode::Vec::new();
+            {
+                let __encode_as_type_vals = &mut __encode_as_type_vec;
+                #( #pushes )*
+            }
+            let __encode_as_type_composite =
+                #path_to_scale_encode::Composite::new(__encode_as_type_vec.into_iter());
+            if __encode_as_type_skipped {
+                __encode_as_type_composite.require_named_target()
+            } else {
+                __encode_as_type_composite
+            }
+        });
+        return (matcher, composite);
+    }
+
+    let composite = match fields {
+        syn::Fields::Named(fields) => {
+            let tuple_body = fields
+                .named
                 .iter()
-                .filter(|f| !should_skip(&f.attrs))
+                .filter(|f| !should_skip_field(f))
                 .map(|f| {
-                    let field_name_str = f.ident.as_ref().unwrap().to_string();
-                    let field_name = &f.ident;
-                    quote!((Some(#field_name_str), #path_to_scale_encode::CompositeField::new(#field_name)))
+                    let field_name_str = named_field_name(f);
+                    let field_name = f.ident.clone().unwrap();
+                    let composite_field = composite_field(path_to_scale_encode, f, &field_name);
+                    quote!((Some(#field_name_str), #composite_field))
                 });
-
-            (
-                quote!({#( #match_body ),*}),
-                quote!(#path_to_scale_encode::Composite::new([#( #tuple_body ),*].into_iter())),
-            )
+            quote!(#path_to_scale_encode::Composite::new([#( #tuple_body ),*].into_iter()))
         }
         syn::Fields::Unnamed(fields) => {
-            let field_idents = fields
+            let tuple_body = fields
                 .unnamed
                 .iter()
                 .enumerate()
-                .map(|(idx, f)| (format_ident!("_{idx}"), f));
+                .filter(|(_, f)| !should_skip_field(f))
+                .map(|(idx, f)| {
+                    let i = format_ident!("_{idx}");
+                    let composite_field = composite_field(path_to_scale_encode, f, &i);
+                    quote!((None as Option<&'static str>, #composite_field))
+                });
+            quote!(#path_to_scale_encode::Composite::new([#( #tuple_body ),*].into_iter()))
+        }
+        syn::Fields::Unit => {
+            quote!(#path_to_scale_encode::Composite::new(([] as [(Option<&'static str>, #path_to_scale_encode::CompositeField<_>);0]).into_iter()))
+        }
+    };
 
-            let match_body = field_idents.clone().map(|(i, _)| quote!(#i));
-            let tuple_body = field_idents
-                .filter(|(_, f)| !should_skip(&f.attrs))
-                .map(|(i, _)| quote!((None as Option<&'static str>, #path_to_scale_encode::CompositeField::new(#i))));
+    (matcher, composite)
+}
 
-            (
-                quote!((#( #match_body ),*)),
-                quote!(#path_to_scale_encode::Composite::new([#( #tuple_body ),*].into_iter())),
-            )
+// Generate statements which push each (non skipped) field into `__encode_as_type_vals`,
+// splicing in the fields of any flattened fields and leaving out any fields whose
+// `skip_if` function returns true. `__encode_as_type_skipped` is set if any fields
+// (including those of flattened fields) are left out. The fields are expected to have been
+// bound to variables using the matcher from `fields_to_matcher_and_composite`.
+fn fields_to_pushes(path_to_scale_encode: &syn::Path, fields: &syn::Fields) -> Vec<TokenStream2> {
+    fields
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, f)| {
+            if should_skip_field(f) {
+                return None;
+            }
+            let attrs = field_attrs(&f.attrs);
+
+            let (field_name, field_name_str) = match &f.ident {
+                Some(ident) => {
+                    let name = named_field_name(f);
+                    (ident.clone(), quote!(Some(#name)))
+                }
+                None => (format_ident!("_{idx}"), quote!(None as Option<&'static str>)),
+            };
+
+            let push = if attrs.flatten {
+                quote!(
+                    if #path_to_scale_encode::FlattenFields::flatten_fields(#field_name, __encode_as_type_vals) {
+                        __encode_as_type_skipped = true;
+                    }
+                )
+            } else {
+                let composite_field = composite_field(path_to_scale_encode, f, &field_name);
+                quote!(__encode_as_type_vals.push((#field_name_str, #composite_field));)
+            };
+            let push = match &attrs.skip_if {
+                Some(skip_if) => quote!(
+                    if #skip_if(#field_name) {
+                        __encode_as_type_skipped = true;
+                    } else {
+                        #push
+                    }
+                ),
+                None => push,
+            };
+            Some(push)
+        })
+        .collect()
+}
+
+// Generate statements which wrap each (non skipped) field marked with `with` in an adapter
+// which encodes it using the given function. The fields are expected to have been bound to
+// variables using the matcher from `fields_to_matcher_and_composite`, and the adapters are
+// referred to by `composite_field`.
+fn fields_to_with_bindings(path_to_scale_encode: &syn::Path, fields: &syn::Fields) -> TokenStream2 {
+    let bindings = fields.iter().enumerate().filter_map(|(idx, f)| {
+        if should_skip_field(f) {
+            return None;
+        }
+        let with = field_attrs(&f.attrs).with?;
+        let field_name = match &f.ident {
+            Some(ident) => ident.clone(),
+            None => format_ident!("_{idx}"),
+        };
+        let adapter_name = format_ident!("__encode_as_type_with_{field_name}");
+        Some(quote!(
+            let #adapter_name = #path_to_scale_encode::EncodeFieldWith::<_, ScaleEncodeResolver>::new(#field_name, #with);
+        ))
+    });
+    quote!(#( #bindings )*)
+}
+
+// An expression constructing a CompositeField for the field bound to `field_name`, using
+// the adapter from `fields_to_with_bindings` if the field is encoded `with` some function.
+fn composite_field(
+    path_to_scale_encode: &syn::Path,
+    field: &syn::Field,
+    field_name: &syn::Ident,
+) -> TokenStream2 {
+    if field_attrs(&field.attrs).with.is_some() {
+        let adapter_name = format_ident!("__encode_as_type_with_{field_name}");
+        quote!(#path_to_scale_encode::CompositeField::new_dyn(&#adapter_name))
+    } else {
+        quote!(#path_to_scale_encode::CompositeField::new(#field_name))
+    }
+}
+
+// The name that a named field will be encoded as, which is its Rust ident unless
+// it has been renamed.
+fn named_field_name(field: &syn::Field) -> String {
+    match field_attrs(&field.attrs).rename {
+        Some(name) => name,
+        None => field.ident.as_ref().unwrap().to_string(),
+    }
+}
+
+// Flattened fields are encoded via their own FlattenFields impl, so complain if they are
+// also given a function to encode them `with`.
+fn check_field_attrs(fields: &syn::Fields) -> syn::Result<()> {
+    for field in fields {
+        let attrs = field_attrs(&field.attrs);
+        if attrs.flatten && attrs.with.is_some() {
+            return Err(syn::Error::new_spanned(
+                field,
+                "#[encode_as_type(with = \"..\")] cannot be used on flattened fields",
+            ));
         }
-        syn::Fields::Unit => (
-            quote!(),
-            quote!(#path_to_scale_encode::Composite::new(([] as [(Option<&'static str>, #path_to_scale_encode::CompositeField<_>);0]).into_iter())),
-        ),
     }
+    check_unnamed_field_attrs(fields)
+}
+
+// Names are never used for unnamed fields, so complain if any are renamed. Skipping
+// them conditionally would also shift the position of every field after them, which
+// would lead to values being encoded into the wrong fields, so complain about that too.
+fn check_unnamed_field_attrs(fields: &syn::Fields) -> syn::Result<()> {
+    let syn::Fields::Unnamed(fields) = fields else {
+        return Ok(());
+    };
+    for field in &fields.unnamed {
+        let attrs = field_attrs(&field.attrs);
+        if attrs.rename.is_some() {
+            return Err(syn::Error::new_spanned(
+                field,
+                "#[encode_as_type(rename = \"..\")] can only be used on named fields",
+            ));
+        }
+        if attrs.skip_if.is_some() {
+            return Err(syn::Error::new_spanned(
+                field,
+                "#[encode_as_type(skip_if = \"..\")] can only be used on named fields",
+            ));
+        }
+    }
+    Ok(())
 }
 
 struct TopLevelAttrs {
@@ -233,6 +686,10 @@ struct TopLevelAttrs {
     crate_path: syn::Path,
     // allow custom trait bounds to be used instead of the defaults.
     trait_bounds: Option<Punctuated<syn::WherePredicate, syn::Token!(,)>>,
+    // encode fieldless enums as their discriminant when the target isn't a variant.
+    discriminant: bool,
+    // don't generate an EncodeAsFields impl for structs.
+    no_fields: bool,
 }
 
 impl TopLevelAttrs {
@@ -242,14 +699,20 @@ impl TopLevelAttrs {
         #[derive(FromMeta)]
         struct TopLevelAttrsInner {
             #[darling(default)]
-            crate_path: Option<syn::Path>,
+            crate_path: Option<syn::LitStr>,
             #[darling(default)]
             trait_bounds: Option<Punctuated<syn::WherePredicate, syn::Token!(,)>>,
+            #[darling(default)]
+            discriminant: bool,
+            #[darling(default)]
+            no_fields: bool,
         }
 
         let mut res = TopLevelAttrs {
             crate_path: syn::parse_quote!(::scale_encode),
             trait_bounds: None,
+            discriminant: false,
+            no_fields: false,
         };
 
         // look at each top level attr. parse any for encode_as_type.
@@ -261,8 +724,10 @@ impl TopLevelAttrs {
             let parsed_attrs = TopLevelAttrsInner::from_meta(meta)?;
 
             res.trait_bounds = parsed_attrs.trait_bounds;
+            res.discriminant |= parsed_attrs.discriminant;
+            res.no_fields |= parsed_attrs.no_fields;
             if let Some(crate_path) = parsed_attrs.crate_path {
-                res.crate_path = crate_path;
+                res.crate_path = parse_crate_path(&crate_path)?;
             }
         }
 
@@ -270,17 +735,111 @@ impl TopLevelAttrs {
     }
 }
 
+// Parse the value given to `crate_path`, pointing at it with a helpful error if it's not a path.
+fn parse_crate_path(lit: &syn::LitStr) -> darling::Result<syn::Path> {
+    lit.parse().map_err(|_| {
+        darling::Error::custom(format!(
+            "`crate_path` must be a path to the scale_encode crate, like \"::scale_encode\", but got \"{}\"",
+            lit.value()
+        ))
+        .with_span(lit)
+    })
+}
+
+// Attributes that can be given on fields and variants (`skip`, `rename`), just fields
+// (`flatten`, `bound`, `skip_if`, `with`) or just variants (`ignore_trailing_fields`).
+#[derive(FromAttributes, Default)]
+#[darling(attributes(encode_as_type))]
+struct FieldAttrs {
+    // `skip` or `skip = true/false` as given, which is `None` if it wasn't given at all.
+    #[darling(default, rename = "skip")]
+    skip_given: Option<bool>,
+    // Whether to skip this, taking into account any `#[codec(skip)]` attribute.
+    #[darling(skip)]
+    skip: bool,
+    #[darling(default)]
+    flatten: bool,
+    #[darling(default)]
+    ignore_trailing_fields: bool,
+    #[darling(default)]
+    rename: Option<String>,
+    #[darling(default)]
+    bound: Option<Punctuated<syn::WherePredicate, syn::Token!(,)>>,
+    #[darling(default)]
+    skip_if: Option<syn::Path>,
+    #[darling(default)]
+    with: Option<syn::Path>,
+}
+
+fn field_attrs(attrs: &[syn::Attribute]) -> FieldAttrs {
+    let mut field_attrs = FieldAttrs::from_attributes(attrs)
+        .expect("attributes are checked by check_field_attrs_parse before generating any code");
+    field_attrs.skip = field_attrs.skip_given.unwrap_or(false) || has_codec_skip(attrs);
+    field_attrs
+}
+
+// Check that the attributes given on each field and variant are valid, so that we can
+// report any problems with them up front rather than ignoring them.
+fn check_field_attrs_parse(input: &DeriveInput) -> darling::Result<()> {
+    let mut errors = darling::Error::accumulator();
+    let mut check = |attrs: &[syn::Attribute]| {
+        errors.handle(FieldAttrs::from_attributes(attrs));
+    };
+    match &input.data {
+        syn::Data::Enum(details) => {
+            for variant in &details.variants {
+                check(&variant.attrs);
+                variant.fields.iter().for_each(|f| check(&f.attrs));
+            }
+        }
+        syn::Data::Struct(details) => details.fields.iter().for_each(|f| check(&f.attrs)),
+        syn::Data::Union(_) => {}
+    }
+    errors.finish()
+}
+
+// Checks if any `#[codec(..)]` attributes contain `skip`. Any other codec attributes are
+// ignored here and left for the codec derive macros to make sense of.
+fn has_codec_skip(attrs: &[syn::Attribute]) -> bool {
+    let mut skip = false;
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("codec")) {
+        let _ = attr.parse_nested_meta(|meta| {
+            skip |= meta.path.is_ident("skip");
+            // Consume any value given so that we can carry on to the next item.
+            if meta.input.peek(syn::Token![=]) {
+                meta.value()?.parse::<syn::Expr>()?;
+            } else if meta.input.peek(syn::token::Paren) {
+                let content;
+                syn::parenthesized!(content in meta.input);
+                content.parse::<TokenStream2>()?;
+            }
+            Ok(())
+        });
+    }
+    skip
+}
+
 // Checks if the attributes contain `skip`.
-//
-// NOTE: Since we only care about `skip` at the moment, we just expose this helper,
-// but if we add more attrs we can expose `FieldAttrs` properly:
 fn should_skip(attrs: &[syn::Attribute]) -> bool {
-    #[derive(FromAttributes, Default)]
-    #[darling(attributes(encode_as_type, codec))]
-    struct FieldAttrs {
-        #[darling(default)]
-        skip: bool,
-    }
+    field_attrs(attrs).skip
+}
 
-    FieldAttrs::from_attributes(attrs).unwrap_or_default().skip
+// Fields are skipped if they are marked with `skip`, or are `PhantomData` and not marked
+// with `skip = false`, since these encode to nothing anyway (scale-info leaves them out of
+// type information, too).
+fn should_skip_field(field: &syn::Field) -> bool {
+    let attrs = field_attrs(&field.attrs);
+    attrs.skip || (is_phantom_data(&field.ty) && attrs.skip_given != Some(false))
+}
+
+// Is the type `PhantomData<..>`? We can only go by its name here.
+fn is_phantom_data(ty: &syn::Type) -> bool {
+    match ty {
+        syn::Type::Path(ty) => ty
+            .path
+            .segments
+            .last()
+            .is_some_and(|s| s.ident == "PhantomData"),
+        _ => false,
+    }
 }
@@ -0,0 +1,123 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd. (admin@parity.io)
+// This file is a part of the scale-encode crate.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//         http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use scale_encode::{testing::make_type, EncodeAsType, SequenceTarget};
+use std::hint::black_box;
+
+// Encoding into an empty output means that it has to grow to fit the whole sequence,
+// unless we reserve enough space up front.
+fn encode_bytes(c: &mut Criterion) {
+    let bytes = vec![1u8; 1_000_000];
+    let (type_id, types) = make_type::<Vec<u8>>();
+
+    c.bench_function("encode 1M bytes into Vec<u8>", |b| {
+        b.iter(|| black_box(&bytes).encode_as_type(type_id, &types).unwrap())
+    });
+}
+
+// Byte arrays like hashes and signatures going into arrays of bytes of the same length are
+// copied straight to the output, whereas other targets need each byte to be encoded
+// individually.
+fn encode_byte_arrays(c: &mut Criterion) {
+    let hash = [1u8; 32];
+    let (type_id, types) = make_type::<[u8; 32]>();
+    c.bench_function("encode [u8; 32] into [u8; 32]", |b| {
+        b.iter(|| black_box(&hash).encode_as_type(type_id, &types).unwrap())
+    });
+
+    let (type_id, types) = make_type::<[u16; 32]>();
+    c.bench_function("encode [u8; 32] into [u16; 32]", |b| {
+        b.iter(|| black_box(&hash).encode_as_type(type_id, &types).unwrap())
+    });
+
+    let signature = [1u8; 64];
+    let (type_id, types) = make_type::<[u8; 64]>();
+    c.bench_function("encode [u8; 64] into [u8; 64]", |b| {
+        b.iter(|| {
+            black_box(&signature)
+                .encode_as_type(type_id, &types)
+                .unwrap()
+        })
+    });
+}
+
+// Elements of a fixed encoded size let us reserve space for the whole sequence up front,
+// even when each element is encoded individually.
+fn encode_fixed_size_values(c: &mut Criterion) {
+    let values: Vec<(u16, u16)> = (0..100_000).map(|n| (n as u16, 1)).collect();
+    let (type_id, types) = make_type::<Vec<(u32, u32)>>();
+
+    c.bench_function("encode 100k (u16, u16) into Vec<(u32, u32)>", |b| {
+        b.iter(|| black_box(&values).encode_as_type(type_id, &types).unwrap())
+    });
+}
+
+// Numbers going into a sequence of the same primitive type are copied straight to the
+// output, whereas other targets need each number to be encoded individually.
+fn encode_numbers(c: &mut Criterion) {
+    let numbers: Vec<u64> = (0..100_000).collect();
+
+    let (type_id, types) = make_type::<Vec<u64>>();
+    c.bench_function("encode 100k u64s into Vec<u64>", |b| {
+        b.iter(|| black_box(&numbers).encode_as_type(type_id, &types).unwrap())
+    });
+
+    let (type_id, types) = make_type::<Vec<u128>>();
+    c.bench_function("encode 100k u64s into Vec<u128>", |b| {
+        b.iter(|| black_box(&numbers).encode_as_type(type_id, &types).unwrap())
+    });
+}
+
+// A SequenceTarget resolves the sequence type once up front, rather than each time that
+// a sequence is encoded into it.
+fn encode_many_sequences(c: &mut Criterion) {
+    let column: Vec<Vec<u16>> = (0..1000).map(|n| vec![n; 10]).collect();
+    let (type_id, types) = make_type::<Vec<u32>>();
+
+    c.bench_function("encode 1000 sequences into Vec<u32>", |b| {
+        b.iter(|| {
+            let mut out = Vec::new();
+            for values in black_box(&column) {
+                values.encode_as_type_to(type_id, &types, &mut out).unwrap();
+            }
+            out
+        })
+    });
+
+    let target = SequenceTarget::new(type_id, &types).unwrap();
+    c.bench_function(
+        "encode 1000 sequences into a Vec<u32> SequenceTarget",
+        |b| {
+            b.iter(|| {
+                let mut out = Vec::new();
+                for values in black_box(&column) {
+                    target.encode_iter_to(values, &types, &mut out).unwrap();
+                }
+                out
+            })
+        },
+    );
+}
+
+criterion_group!(
+    benches,
+    encode_bytes,
+    encode_byte_arrays,
+    encode_fixed_size_values,
+    encode_numbers,
+    encode_many_sequences
+);
+criterion_main!(benches);
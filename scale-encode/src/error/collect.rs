@@ -0,0 +1,81 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd. (admin@parity.io)
+// This file is a part of the scale-encode crate.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//         http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Support for `EncodeAsType::encode_as_type_collect_errors()` (which needs the `std` feature),
+//! which carries on encoding past errors in nested values in order to report all of them at once.
+
+use super::Error;
+
+#[cfg(feature = "std")]
+std::thread_local! {
+    static ERRORS: core::cell::RefCell<Option<alloc::vec::Vec<Error>>> =
+        const { core::cell::RefCell::new(None) };
+}
+
+// Run the closure, collecting and returning any errors recorded while it runs.
+#[cfg(feature = "std")]
+pub(crate) fn collect<T>(f: impl FnOnce() -> T) -> (T, alloc::vec::Vec<Error>) {
+    let prev = ERRORS.with(|errors| errors.borrow_mut().replace(alloc::vec::Vec::new()));
+    let res = f();
+    let errors = ERRORS.with(|errors| core::mem::replace(&mut *errors.borrow_mut(), prev));
+    (res, errors.unwrap_or_default())
+}
+
+// Run the closure without collecting errors. This is for code which tries encoding a
+// value in different ways, and relies on errors being returned to decide which works.
+#[cfg(feature = "std")]
+pub(crate) fn suspended<T>(f: impl FnOnce() -> T) -> T {
+    let prev = ERRORS.with(|errors| errors.borrow_mut().take());
+    let res = f();
+    ERRORS.with(|errors| *errors.borrow_mut() = prev);
+    res
+}
+#[cfg(not(feature = "std"))]
+pub(crate) fn suspended<T>(f: impl FnOnce() -> T) -> T {
+    f()
+}
+
+// Encode some nested value using the closure, adding context to any error it returns.
+// If we're collecting errors, then any errors recorded while encoding the nested value
+// are given the same context, and an error is recorded rather than returned so that
+// encoding carries on. The output will be invalid in this case, but is never returned.
+#[cfg(feature = "std")]
+pub(crate) fn nested(
+    f: impl FnOnce() -> Result<(), Error>,
+    add_context: impl Fn(Error) -> Error,
+) -> Result<(), Error> {
+    let Some(before) = ERRORS.with(|errors| errors.borrow().as_ref().map(|e| e.len())) else {
+        return f().map_err(add_context);
+    };
+
+    let res = f();
+    ERRORS.with(|errors| {
+        let mut errors = errors.borrow_mut();
+        let errors = errors.as_mut().expect("still collecting errors");
+        let nested_errors: alloc::vec::Vec<_> = errors.drain(before..).map(&add_context).collect();
+        errors.extend(nested_errors);
+        if let Err(e) = res {
+            errors.push(add_context(e));
+        }
+    });
+    Ok(())
+}
+#[cfg(not(feature = "std"))]
+pub(crate) fn nested(
+    f: impl FnOnce() -> Result<(), Error>,
+    add_context: impl Fn(Error) -> Error,
+) -> Result<(), Error> {
+    f().map_err(add_context)
+}
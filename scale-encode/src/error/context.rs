@@ -39,25 +39,41 @@ impl Context {
     pub fn path(&self) -> Path<'_> {
         Path(Cow::Borrowed(&self.path))
     }
+    /// Return the number of locations in this context.
+    pub fn len(&self) -> usize {
+        self.path.len()
+    }
+    /// Return true if this context contains no locations.
+    pub fn is_empty(&self) -> bool {
+        self.path.is_empty()
+    }
+    /// Return each location in this context, outermost first. This is
+    /// the same order that they are displayed in when using [`Context::path()`].
+    pub fn locations(&self) -> impl Iterator<Item = &Location> {
+        self.path.iter().rev()
+    }
 }
 
 /// The current path that we're trying to encode.
-pub struct Path<'a>(Cow<'a, Vec<Location>>);
+pub struct Path<'a>(Cow<'a, [Location]>);
 
 impl<'a> Path<'a> {
     /// Cheaply convert the path to an owned version.
     pub fn into_owned(self) -> Path<'static> {
         Path(Cow::Owned(self.0.into_owned()))
     }
-    /// Return each location visited, oldest first
+    /// Return each location visited, outermost first. This is the
+    /// same order that they are displayed in.
     pub fn locations(&self) -> impl Iterator<Item = &Location> {
-        self.0.iter()
+        // Locations are pushed as errors bubble up, so the most
+        // recently pushed location is the outermost one.
+        self.0.iter().rev()
     }
 }
 
 impl<'a> core::fmt::Display for Path<'a> {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        for (idx, loc) in self.0.iter().enumerate() {
+        for (idx, loc) in self.locations().enumerate() {
             if idx != 0 {
                 f.write_str(".")?;
             }
@@ -103,4 +119,73 @@ impl Location {
             inner: Loc::Index(i),
         }
     }
+    /// If this location represents a struct field, return its name.
+    pub fn as_field(&self) -> Option<&str> {
+        match &self.inner {
+            Loc::Field(name) => Some(name),
+            _ => None,
+        }
+    }
+    /// If this location represents a variant, return its name.
+    pub fn as_variant(&self) -> Option<&str> {
+        match &self.inner {
+            Loc::Variant(name) => Some(name),
+            _ => None,
+        }
+    }
+    /// If this location represents a tuple or array index, return it.
+    pub fn as_idx(&self) -> Option<usize> {
+        match &self.inner {
+            Loc::Index(i) => Some(*i),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloc::string::ToString;
+
+    #[test]
+    fn context_len_and_locations() {
+        let mut ctx = Context::new();
+        assert!(ctx.is_empty());
+        assert_eq!(ctx.len(), 0);
+
+        // Locations are pushed innermost first as errors bubble up:
+        ctx.push(Location::idx(1));
+        ctx.push(Location::field("bar"));
+        ctx.push(Location::variant("Foo"));
+
+        assert!(!ctx.is_empty());
+        assert_eq!(ctx.len(), 3);
+        assert_eq!(
+            ctx.locations().cloned().collect::<Vec<_>>(),
+            [
+                Location::variant("Foo"),
+                Location::field("bar"),
+                Location::idx(1)
+            ]
+        );
+        assert_eq!(ctx.path().to_string(), "(Foo).bar.[1]");
+    }
+
+    #[test]
+    fn location_accessors() {
+        let field = Location::field("foo");
+        assert_eq!(field.as_field(), Some("foo"));
+        assert_eq!(field.as_variant(), None);
+        assert_eq!(field.as_idx(), None);
+
+        let variant = Location::variant("Bar");
+        assert_eq!(variant.as_field(), None);
+        assert_eq!(variant.as_variant(), Some("Bar"));
+        assert_eq!(variant.as_idx(), None);
+
+        let idx = Location::idx(3);
+        assert_eq!(idx.as_field(), None);
+        assert_eq!(idx.as_variant(), None);
+        assert_eq!(idx.as_idx(), Some(3));
+    }
 }
@@ -14,6 +14,7 @@
 // limitations under the License.
 
 //! An error that is emitted whenever some encoding fails.
+pub(crate) mod collect;
 mod context;
 
 use alloc::{borrow::Cow, boxed::Box, string::String};
@@ -21,6 +22,16 @@ use core::fmt::Display;
 
 pub use context::{Context, Location};
 
+/// Run the given closure without collecting errors, even if we're in the middle of a call to
+/// `EncodeAsType::encode_as_type_collect_errors()` (which needs the `std` feature). While errors are being collected,
+/// nested values which fail to encode record their error and then report success, so that
+/// encoding carries on. Implementations which try encoding a value one way and fall back to
+/// another way if that fails should make the first attempt inside this closure, so that they
+/// see any error. When errors aren't being collected, this just runs the closure.
+pub fn suspend_error_collection<T>(f: impl FnOnce() -> T) -> T {
+    collect::suspended(f)
+}
+
 /// An error produced while attempting to encode some type.
 #[derive(Debug)]
 pub struct Error {
@@ -125,6 +136,17 @@ pub enum ErrorKind {
         /// Identifier for the expected type
         expected_id: String,
     },
+    /// Like [`ErrorKind::WrongShape`], but for mistakes which are easy to make, and so this
+    /// comes with a hint about what to do instead.
+    #[error("Cannot encode {actual:?} into type with ID {expected_id}; {hint}")]
+    WrongShapeWithHint {
+        /// The actual kind we have to encode
+        actual: Kind,
+        /// Identifier for the expected type
+        expected_id: String,
+        /// Some guidance on why this doesn't work, or what might work instead.
+        hint: String,
+    },
     /// The types line up, but the expected length of the target type is different from the length of the input value.
     #[error("Cannot encode to type; expected length {expected_len} but got length {actual_len}")]
     WrongLength {
@@ -155,6 +177,10 @@ pub enum ErrorKind {
         /// Name of the field which was not provided.
         name: String,
     },
+    /// The target type has unnamed fields, but our fields can only be lined up by name; see
+    /// [`crate::Composite::require_named_target()`].
+    #[error("Cannot line up fields by position; the target type needs named fields")]
+    CannotLineUpFieldsByPosition,
     /// A custom error.
     #[error("Custom error: {0}")]
     Custom(Box<dyn core::error::Error + Send + Sync + 'static>),
@@ -0,0 +1,293 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd. (admin@parity.io)
+// This file is a part of the scale-encode crate.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//         http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{error::Error, EncodeAsType};
+use alloc::{format, vec::Vec};
+use scale_type_resolver::TypeResolver;
+
+/// A hex encoded string (optionally prefixed with `0x`) which is decoded into bytes
+/// and then encoded into the target type in the same way as a `&[u8]` would be. This
+/// is useful for encoding things like hashes and account IDs into byte sequences or arrays.
+///
+/// An error is returned if the string is not valid hex.
+///
+/// ```rust
+/// use scale_encode::{EncodeAsType, Hex};
+/// use scale_info::PortableRegistry;
+///
+/// let m = scale_info::MetaType::new::<[u8; 4]>();
+/// let mut types = scale_info::Registry::new();
+/// let type_id = types.register_type(&m).id;
+/// let types: PortableRegistry = types.into();
+///
+/// let bytes = Hex("0xdeadbeef").encode_as_type(type_id, &types).unwrap();
+/// assert_eq!(bytes, vec![0xde, 0xad, 0xbe, 0xef]);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Hex<'a>(pub &'a str);
+
+impl EncodeAsType for Hex<'_> {
+    fn encode_as_type_to<R: TypeResolver>(
+        &self,
+        type_id: R::TypeId,
+        types: &R,
+        out: &mut Vec<u8>,
+    ) -> Result<(), Error> {
+        decode_hex(self.0)?.encode_as_type_to(type_id, types, out)
+    }
+}
+
+/// A wrapper around anything which can provide a slice of bytes (via [`AsRef<[u8]>`]), which
+/// is encoded into the target type in the same way as a `&[u8]` would be. This is useful for
+/// encoding byte providing types, such as the bytes of a `String`, into byte sequences or arrays.
+///
+/// ```rust
+/// use scale_encode::{AsBytes, EncodeAsType};
+/// use scale_info::PortableRegistry;
+///
+/// let m = scale_info::MetaType::new::<Vec<u8>>();
+/// let mut types = scale_info::Registry::new();
+/// let type_id = types.register_type(&m).id;
+/// let types: PortableRegistry = types.into();
+///
+/// let bytes = AsBytes(String::from("hello")).encode_as_type(type_id, &types).unwrap();
+/// assert_eq!(bytes, codec::Encode::encode(&b"hello".to_vec()));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AsBytes<T>(pub T);
+
+impl<T: AsRef<[u8]>> EncodeAsType for AsBytes<T> {
+    fn encode_as_type_to<R: TypeResolver>(
+        &self,
+        type_id: R::TypeId,
+        types: &R,
+        out: &mut Vec<u8>,
+    ) -> Result<(), Error> {
+        self.0.as_ref().encode_as_type_to(type_id, types, out)
+    }
+}
+
+fn decode_hex(s: &str) -> Result<Vec<u8>, Error> {
+    let hex = s.strip_prefix("0x").unwrap_or(s).as_bytes();
+    if hex.len() % 2 != 0 {
+        return Err(Error::custom_string(format!(
+            "Cannot decode hex string {s}; it has an odd number of digits"
+        )));
+    }
+
+    let digit = |idx: usize| {
+        let c = hex[idx];
+        (c as char).to_digit(16).ok_or_else(|| {
+            Error::custom_string(format!(
+                "Cannot decode hex string {s}; {:?} is not a valid hex digit",
+                c as char
+            ))
+        })
+    };
+
+    (0..hex.len())
+        .step_by(2)
+        .map(|idx| Ok((digit(idx)? * 16 + digit(idx + 1)?) as u8))
+        .collect()
+}
+
+/// The alphabet used by a [`Base64`] encoded string.
+#[cfg(feature = "base64")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Base64Alphabet {
+    /// The standard alphabet, which uses `+` and `/`.
+    Standard,
+    /// The URL safe alphabet, which uses `-` and `_`.
+    UrlSafe,
+}
+
+/// A base64 encoded string which is decoded into bytes and then encoded into the target
+/// type in the same way as a `&[u8]` would be. Padding is optional.
+///
+/// An error is returned if the string is not valid base64 in the chosen alphabet.
+///
+/// ```rust
+/// use scale_encode::{Base64, EncodeAsType};
+/// use scale_info::PortableRegistry;
+///
+/// let m = scale_info::MetaType::new::<Vec<u8>>();
+/// let mut types = scale_info::Registry::new();
+/// let type_id = types.register_type(&m).id;
+/// let types: PortableRegistry = types.into();
+///
+/// let bytes = Base64::new("aGVsbG8=").encode_as_type(type_id, &types).unwrap();
+/// assert_eq!(bytes, codec::Encode::encode(&b"hello".to_vec()));
+/// ```
+#[cfg(feature = "base64")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Base64<'a> {
+    /// The base64 encoded string.
+    pub data: &'a str,
+    /// The alphabet that the string is encoded with.
+    pub alphabet: Base64Alphabet,
+}
+
+#[cfg(feature = "base64")]
+impl<'a> Base64<'a> {
+    /// Construct a new [`Base64`] string which uses the standard alphabet.
+    pub fn new(data: &'a str) -> Self {
+        Base64 {
+            data,
+            alphabet: Base64Alphabet::Standard,
+        }
+    }
+    /// Construct a new [`Base64`] string which uses the URL safe alphabet.
+    pub fn url_safe(data: &'a str) -> Self {
+        Base64 {
+            data,
+            alphabet: Base64Alphabet::UrlSafe,
+        }
+    }
+}
+
+#[cfg(feature = "base64")]
+impl EncodeAsType for Base64<'_> {
+    fn encode_as_type_to<R: TypeResolver>(
+        &self,
+        type_id: R::TypeId,
+        types: &R,
+        out: &mut Vec<u8>,
+    ) -> Result<(), Error> {
+        use base64::{alphabet, engine, Engine};
+
+        let config = engine::GeneralPurposeConfig::new()
+            .with_decode_padding_mode(engine::DecodePaddingMode::Indifferent);
+        let alphabet = match self.alphabet {
+            Base64Alphabet::Standard => &alphabet::STANDARD,
+            Base64Alphabet::UrlSafe => &alphabet::URL_SAFE,
+        };
+
+        let bytes = engine::GeneralPurpose::new(alphabet, config)
+            .decode(self.data)
+            .map_err(|e| {
+                Error::custom_string(format!("Cannot decode base64 string {}: {e}", self.data))
+            })?;
+        bytes.encode_as_type_to(type_id, types, out)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::error::ErrorKind;
+    use crate::testing::encode_type;
+    use alloc::string::ToString;
+    use alloc::vec;
+    use codec::Encode;
+
+    #[test]
+    fn hex_encodes_to_byte_sequences_and_arrays() {
+        let expected = vec![0xdeu8, 0xad, 0xbe, 0xef];
+        assert_eq!(
+            encode_type::<_, Vec<u8>>(&Hex("0xdeadbeef")).unwrap(),
+            expected.encode()
+        );
+        assert_eq!(
+            encode_type::<_, Vec<u8>>(&Hex("DEADBEEF")).unwrap(),
+            expected.encode()
+        );
+        assert_eq!(
+            encode_type::<_, [u8; 4]>(&Hex("deadbeef")).unwrap(),
+            expected
+        );
+        assert_eq!(
+            encode_type::<_, Vec<u8>>(&Hex("0x")).unwrap(),
+            Vec::<u8>::new().encode()
+        );
+    }
+
+    #[test]
+    fn invalid_hex_is_an_error() {
+        let err = encode_type::<_, Vec<u8>>(&Hex("0xdeadbeeg")).unwrap_err();
+        assert!(err.to_string().contains("'g' is not a valid hex digit"));
+
+        let err = encode_type::<_, Vec<u8>>(&Hex("0xdeadbee")).unwrap_err();
+        assert!(err.to_string().contains("odd number of digits"));
+
+        let err = encode_type::<_, [u8; 2]>(&Hex("0xdeadbeef")).unwrap_err();
+        assert!(matches!(
+            err.kind(),
+            ErrorKind::WrongLength {
+                actual_len: 4,
+                expected_len: 2
+            }
+        ));
+    }
+
+    #[test]
+    fn as_bytes_encodes_to_byte_sequences_and_arrays() {
+        let s = AsBytes("abcd".to_string());
+        assert_eq!(
+            encode_type::<_, Vec<u8>>(&s).unwrap(),
+            b"abcd".to_vec().encode()
+        );
+        assert_eq!(encode_type::<_, [u8; 4]>(&s).unwrap(), b"abcd".to_vec());
+
+        let arr = AsBytes([1u8, 2, 3, 4]);
+        assert_eq!(
+            encode_type::<_, Vec<u8>>(&arr).unwrap(),
+            vec![1u8, 2, 3, 4].encode()
+        );
+        assert_eq!(encode_type::<_, [u8; 4]>(&arr).unwrap(), vec![1u8, 2, 3, 4]);
+
+        let err = encode_type::<_, [u8; 2]>(&arr).unwrap_err();
+        assert!(matches!(
+            err.kind(),
+            ErrorKind::WrongLength {
+                actual_len: 4,
+                expected_len: 2
+            }
+        ));
+    }
+
+    #[cfg(feature = "base64")]
+    #[test]
+    fn base64_encodes_to_byte_sequences() {
+        let expected = vec![0xfbu8, 0xff, 0x01].encode();
+        assert_eq!(
+            encode_type::<_, Vec<u8>>(&Base64::new("+/8B")).unwrap(),
+            expected
+        );
+        assert_eq!(
+            encode_type::<_, Vec<u8>>(&Base64::url_safe("-_8B")).unwrap(),
+            expected
+        );
+
+        // Padding is optional:
+        let hello = b"hello".to_vec().encode();
+        assert_eq!(
+            encode_type::<_, Vec<u8>>(&Base64::new("aGVsbG8=")).unwrap(),
+            hello
+        );
+        assert_eq!(
+            encode_type::<_, Vec<u8>>(&Base64::new("aGVsbG8")).unwrap(),
+            hello
+        );
+    }
+
+    #[cfg(feature = "base64")]
+    #[test]
+    fn invalid_base64_is_an_error() {
+        let err = encode_type::<_, Vec<u8>>(&Base64::new("-_8B")).unwrap_err();
+        assert!(err.to_string().contains("Cannot decode base64 string"));
+        let err = encode_type::<_, Vec<u8>>(&Base64::url_safe("+/8B")).unwrap_err();
+        assert!(err.to_string().contains("Cannot decode base64 string"));
+    }
+}
@@ -0,0 +1,415 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd. (admin@parity.io)
+// This file is a part of the scale-encode crate.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//         http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::{Composite, CompositeField, Variant};
+use crate::{
+    error::{collect, Error, ErrorKind, Location},
+    EncodeAsFields, EncodeAsType,
+};
+use alloc::{collections::BTreeMap, format, string::ToString, vec::Vec};
+use core::any::Any;
+use scale_type_resolver::{visitor, Field, FieldIter, TypeResolver};
+
+/// A wrapper around some number which allows it to be encoded into a `bool` target type.
+/// A value of `0` is encoded as `false` and a value of `1` is encoded as `true`. Any other
+/// value will lead to an [`ErrorKind::NumberOutOfRange`] error.
+///
+/// By default, numbers cannot be encoded into `bool` types. This is an opt-in to help
+/// bridge differences in metadata where a flag is represented as a number on one side
+/// and a `bool` on the other.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NumberAsBool<T>(pub T);
+
+impl<T> EncodeAsType for NumberAsBool<T>
+where
+    T: Copy + TryInto<u8> + core::fmt::Display,
+{
+    fn encode_as_type_to<R: TypeResolver>(
+        &self,
+        type_id: R::TypeId,
+        types: &R,
+        out: &mut Vec<u8>,
+    ) -> Result<(), Error> {
+        let b = match self.0.try_into() {
+            Ok(0) => false,
+            Ok(1) => true,
+            _ => {
+                return Err(Error::new(ErrorKind::NumberOutOfRange {
+                    value: self.0.to_string(),
+                    expected_id: format!("{type_id:?}"),
+                }))
+            }
+        };
+        b.encode_as_type_to(type_id, types, out)
+    }
+}
+
+/// A wrapper around some value which ignores the type ID that it's asked to encode into,
+/// and instead encodes the value into the type ID that it's been given here. This is useful
+/// when encoding some value (for instance a field in a struct) against a type that is known
+/// better than the type information being used implies.
+///
+/// The type ID given must be the same type as the [`TypeResolver::TypeId`] used to encode the
+/// value, else an error will be returned.
+///
+/// ```rust
+/// use codec::Encode;
+/// use scale_encode::{EncodeAsType, Tagged};
+/// use scale_info::PortableRegistry;
+///
+/// let m = scale_info::MetaType::new::<(u8, u64)>();
+/// let mut types = scale_info::Registry::new();
+/// let type_id = types.register_type(&m).id;
+/// let types: PortableRegistry = types.into();
+///
+/// // Find the type ID for the u64 in our registry.
+/// let u64_id = types.types.iter().find(|t| t.ty.path.segments.is_empty()
+///     && matches!(t.ty.type_def, scale_info::TypeDef::Primitive(scale_info::TypeDefPrimitive::U64)))
+///     .unwrap()
+///     .id;
+///
+/// // Encode the first tuple value as a u64 rather than a u8:
+/// let bytes = (Tagged::new(1u8, u64_id), 2u8).encode_as_type(type_id, &types).unwrap();
+/// assert_eq!(bytes, (1u64, 2u64).encode());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Tagged<T, Id> {
+    /// The value to encode.
+    pub value: T,
+    /// The type ID to encode the value into.
+    pub type_id: Id,
+}
+
+impl<T, Id> Tagged<T, Id> {
+    /// Construct a new [`Tagged`] value, which will be encoded into the given type ID.
+    pub fn new(value: T, type_id: Id) -> Self {
+        Tagged { value, type_id }
+    }
+}
+
+impl<T, Id> EncodeAsType for Tagged<T, Id>
+where
+    T: EncodeAsType,
+    Id: Clone + 'static,
+{
+    fn encode_as_type_to<R: TypeResolver>(
+        &self,
+        type_id: R::TypeId,
+        types: &R,
+        out: &mut Vec<u8>,
+    ) -> Result<(), Error> {
+        let Some(tagged_type_id) = (&self.type_id as &dyn Any).downcast_ref::<R::TypeId>() else {
+            return Err(Error::custom_string(format!(
+                "Cannot encode tagged value into type with identifier {type_id:?}; the tagged type ID is not a {}",
+                core::any::type_name::<R::TypeId>()
+            )));
+        };
+        self.value
+            .encode_as_type_to(tagged_type_id.clone(), types, out)
+    }
+}
+
+/// A wrapper around some value which, if the value fails to encode, adds the given
+/// [`Location`] to the context of the error that's returned. This is useful when encoding
+/// values as a part of some larger encoder, so that errors can be attributed to the place
+/// in that larger structure that they came from.
+///
+/// ```rust
+/// use scale_encode::{error::Location, EncodeAsType, WithContext};
+/// use scale_info::PortableRegistry;
+///
+/// let m = scale_info::MetaType::new::<u8>();
+/// let mut types = scale_info::Registry::new();
+/// let type_id = types.register_type(&m).id;
+/// let types: PortableRegistry = types.into();
+///
+/// let err = WithContext::new(1000u16, Location::field("amount"))
+///     .encode_as_type(type_id, &types)
+///     .unwrap_err();
+///
+/// assert_eq!(err.context().path().to_string(), "amount");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WithContext<T> {
+    /// The value to encode.
+    pub value: T,
+    /// The location to attribute any encoding errors to.
+    pub location: Location,
+}
+
+impl<T> WithContext<T> {
+    /// Construct a new [`WithContext`] value, which will attribute any errors
+    /// encoding the value to the given location.
+    pub fn new(value: T, location: Location) -> Self {
+        WithContext { value, location }
+    }
+}
+
+impl<T: EncodeAsType> EncodeAsType for WithContext<T> {
+    fn encode_as_type_to<R: TypeResolver>(
+        &self,
+        type_id: R::TypeId,
+        types: &R,
+        out: &mut Vec<u8>,
+    ) -> Result<(), Error> {
+        collect::nested(
+            || self.value.encode_as_type_to(type_id, types, out),
+            |e| e.at(self.location.clone()),
+        )
+    }
+}
+
+/// A wrapper around a [`Result`] whose error type doesn't implement [`EncodeAsType`]. The
+/// `Ok` value is encoded as the `Ok` variant of the target type, as it would be for a
+/// [`Result`], but trying to encode an `Err` value will return an error.
+///
+/// This is useful for types like `Result<T, MyError>` where `MyError` is not encodable, and
+/// only `Ok` values are expected to ever be encoded.
+///
+/// ```rust
+/// use codec::Encode;
+/// use scale_encode::{EncodeAsType, OkOnly};
+/// use scale_info::PortableRegistry;
+///
+/// struct MyError;
+///
+/// let m = scale_info::MetaType::new::<Result<u64, ()>>();
+/// let mut types = scale_info::Registry::new();
+/// let type_id = types.register_type(&m).id;
+/// let types: PortableRegistry = types.into();
+///
+/// let ok = OkOnly(Ok::<u8, MyError>(123));
+/// let bytes = ok.encode_as_type(type_id, &types).unwrap();
+/// assert_eq!(bytes, Ok::<u64, ()>(123).encode());
+///
+/// let err = OkOnly(Err::<u8, MyError>(MyError));
+/// assert!(err.encode_as_type(type_id, &types).is_err());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OkOnly<T, E>(pub Result<T, E>);
+
+impl<T: EncodeAsType, E> EncodeAsType for OkOnly<T, E> {
+    fn encode_as_type_to<R: TypeResolver>(
+        &self,
+        type_id: R::TypeId,
+        types: &R,
+        out: &mut Vec<u8>,
+    ) -> Result<(), Error> {
+        match &self.0 {
+            Ok(v) => Variant {
+                name: "Ok",
+                fields: Composite::new([(None, CompositeField::new(v))].iter().copied()),
+            }
+            .encode_variant_as_type_to(type_id, types, out),
+            Err(_) => Err(Error::custom_string(format!(
+                "Cannot encode the Err variant of an OkOnly value into type with identifier {type_id:?}; only Ok values can be encoded"
+            ))),
+        }
+    }
+}
+
+/// A wrapper around an [`Option`] which encodes it as a sequence or array of zero or one
+/// values, rather than as an `Option` variant. `None` is encoded as an empty sequence (or
+/// a zero length array), and `Some(value)` as a sequence (or array) containing just `value`.
+///
+/// This is useful when the target type represents an optional value as a bounded list of at
+/// most one item, which is common in some metadata.
+///
+/// ```rust
+/// use codec::Encode;
+/// use scale_encode::{EncodeAsType, OptionAsSeq};
+/// use scale_info::PortableRegistry;
+///
+/// let m = scale_info::MetaType::new::<Vec<u64>>();
+/// let mut types = scale_info::Registry::new();
+/// let type_id = types.register_type(&m).id;
+/// let types: PortableRegistry = types.into();
+///
+/// let bytes = OptionAsSeq(Some(1u8)).encode_as_type(type_id, &types).unwrap();
+/// assert_eq!(bytes, vec![1u64].encode());
+///
+/// let bytes = OptionAsSeq(None::<u8>).encode_as_type(type_id, &types).unwrap();
+/// assert_eq!(bytes, Vec::<u64>::new().encode());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OptionAsSeq<T>(pub Option<T>);
+
+impl<T: EncodeAsType> EncodeAsType for OptionAsSeq<T> {
+    fn encode_as_type_to<R: TypeResolver>(
+        &self,
+        type_id: R::TypeId,
+        types: &R,
+        out: &mut Vec<u8>,
+    ) -> Result<(), Error> {
+        let len = usize::from(self.0.is_some());
+        super::encode_iterable_sequence_to(len, self.0.iter(), type_id, types, out)
+    }
+}
+
+/// A sequence of values, given as the number of values and something which can be iterated
+/// over to produce them. This allows values to be encoded as a sequence or array without first
+/// collecting them into a `Vec`.
+///
+/// The length given is used to encode the length prefix of sequences, and to check that the
+/// length of arrays lines up. If the iterator produces a different number of values, then
+/// an [`ErrorKind::WrongLength`] error is returned. No more than one value beyond the given
+/// length is taken from the iterator, so iterators which are too long (or never end) are
+/// caught without iterating over all of their values.
+///
+/// ```rust
+/// use codec::Encode;
+/// use scale_encode::{EncodeAsType, Seq};
+/// use scale_info::PortableRegistry;
+///
+/// let m = scale_info::MetaType::new::<Vec<u64>>();
+/// let mut types = scale_info::Registry::new();
+/// let type_id = types.register_type(&m).id;
+/// let types: PortableRegistry = types.into();
+///
+/// let bytes = Seq(3, (1u8..=3).map(|n| n * 2)).encode_as_type(type_id, &types).unwrap();
+/// assert_eq!(bytes, vec![2u64, 4, 6].encode());
+///
+/// // The iterator must produce exactly as many values as we say it will:
+/// assert!(Seq(2, 1u8..=3).encode_as_type(type_id, &types).is_err());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Seq<I>(pub usize, pub I);
+
+impl<I> EncodeAsType for Seq<I>
+where
+    I: IntoIterator + Clone,
+    I::Item: EncodeAsType,
+{
+    fn encode_as_type_to<R: TypeResolver>(
+        &self,
+        type_id: R::TypeId,
+        types: &R,
+        out: &mut Vec<u8>,
+    ) -> Result<(), Error> {
+        let Seq(len, iter) = self;
+        let start = out.len();
+
+        // Take at most one more value than expected; enough to know that the
+        // iterator is too long without running through the whole thing.
+        let mut actual_len = 0;
+        let iter = iter
+            .clone()
+            .into_iter()
+            .take(len.saturating_add(1))
+            .inspect(|_| actual_len += 1);
+        super::encode_iterable_sequence_to(*len, iter, type_id, types, out)?;
+
+        // If the iterator lied about its length, we've already encoded an invalid
+        // length prefix (or array), so remove what we've encoded and complain.
+        if actual_len != *len {
+            out.truncate(start);
+            return Err(Error::new(ErrorKind::WrongLength {
+                actual_len,
+                expected_len: *len,
+            }));
+        }
+        Ok(())
+    }
+}
+
+/// A wrapper around a [`BTreeMap`] whose keys don't name fields (for instance, numbers), which
+/// encodes the values of the map in key order, lining them up with the fields of the target
+/// type by position. This allows maps like `BTreeMap<u32, T>` to be encoded into tuples, unnamed
+/// composites, sequences and arrays. An [`ErrorKind::CannotFindField`] error is returned if the
+/// target fields are named, since there's no way to line them up with the keys.
+///
+/// Maps whose keys are strings don't need this; they can be encoded as-is, and their keys are
+/// used to line values up with named fields.
+///
+/// ```rust
+/// use codec::Encode;
+/// use scale_encode::{ByKeyOrder, EncodeAsType};
+/// use scale_info::PortableRegistry;
+/// use std::collections::BTreeMap;
+///
+/// let m = scale_info::MetaType::new::<(u8, u64)>();
+/// let mut types = scale_info::Registry::new();
+/// let type_id = types.register_type(&m).id;
+/// let types: PortableRegistry = types.into();
+///
+/// let map = BTreeMap::from([(2u32, 20u16), (1, 10)]);
+/// let bytes = ByKeyOrder(&map).encode_as_type(type_id, &types).unwrap();
+/// assert_eq!(bytes, (10u8, 20u64).encode());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ByKeyOrder<'a, K, V>(pub &'a BTreeMap<K, V>);
+
+impl<K, V: EncodeAsType> ByKeyOrder<'_, K, V> {
+    fn composite<'a, R: TypeResolver + 'a>(
+        &'a self,
+    ) -> Composite<R, impl ExactSizeIterator<Item = (Option<&'a str>, CompositeField<'a, R>)> + Clone>
+    {
+        Composite::new(self.0.values().map(|v| (None, CompositeField::new(v))))
+    }
+}
+
+// The keys of a ByKeyOrder map don't name any fields, so complain if the target fields
+// need to be lined up by name.
+fn check_fields_are_unnamed<'a, TypeId: 'a>(
+    mut fields: impl Iterator<Item = Field<'a, TypeId>>,
+) -> Result<(), Error> {
+    match fields.find_map(|f| f.name) {
+        Some(name) => Err(Error::new(ErrorKind::CannotFindField {
+            name: name.to_string(),
+        })),
+        None => Ok(()),
+    }
+}
+
+impl<K, V: EncodeAsType> EncodeAsType for ByKeyOrder<'_, K, V> {
+    fn encode_as_type_to<R: TypeResolver>(
+        &self,
+        type_id: R::TypeId,
+        types: &R,
+        out: &mut Vec<u8>,
+    ) -> Result<(), Error> {
+        let v = visitor::new((type_id.clone(), out), |(type_id, out), _| {
+            self.composite()
+                .encode_composite_as_type_to(type_id, types, out)
+        })
+        .visit_composite(|(type_id, out), _, fields| {
+            check_fields_are_unnamed(fields)?;
+            self.composite()
+                .encode_composite_as_type_to(type_id, types, out)
+        })
+        .visit_array(|(type_id, out), _, _| {
+            super::encode_iterable_sequence_to(self.0.len(), self.0.values(), type_id, types, out)
+        })
+        .visit_sequence(|(type_id, out), _, _| {
+            super::encode_iterable_sequence_to(self.0.len(), self.0.values(), type_id, types, out)
+        });
+
+        super::resolve_type_and_encode(types, type_id, v)
+    }
+}
+
+impl<K, V: EncodeAsType> EncodeAsFields for ByKeyOrder<'_, K, V> {
+    fn encode_as_fields_to<R: TypeResolver>(
+        &self,
+        fields: &mut dyn FieldIter<'_, R::TypeId>,
+        types: &R,
+        out: &mut Vec<u8>,
+    ) -> Result<(), Error> {
+        let fields = smallvec::SmallVec::<[_; 16]>::from_iter(fields);
+        check_fields_are_unnamed(fields.iter().cloned())?;
+        self.composite()
+            .encode_composite_fields_to(&mut fields.into_iter(), types, out)
+    }
+}
@@ -14,41 +14,19 @@
 // limitations under the License.
 
 use crate::{
-    error::{Error, ErrorKind, Kind, Location},
-    EncodeAsType, Field, FieldIter, TypeResolver,
+    error::{collect, Error, ErrorKind, Kind, Location},
+    DynEncodeAsType, EncodeAsType, Field, FieldIter, TypeResolver,
 };
 use alloc::collections::BTreeMap;
 use alloc::{format, string::ToString, vec::Vec};
 use scale_type_resolver::visitor;
 
-/// This trait exists to get around object safety issues using [`EncodeAsType`].
-/// It's object safe and automatically implemented for any type which implements
-/// [`EncodeAsType`]. We need this to construct generic [`Composite`] types.
-trait EncodeAsTypeWithResolver<R: TypeResolver> {
-    fn encode_as_type_with_resolver_to(
-        &self,
-        type_id: R::TypeId,
-        types: &R,
-        out: &mut Vec<u8>,
-    ) -> Result<(), Error>;
-}
-impl<T: EncodeAsType, R: TypeResolver> EncodeAsTypeWithResolver<R> for T {
-    fn encode_as_type_with_resolver_to(
-        &self,
-        type_id: R::TypeId,
-        types: &R,
-        out: &mut Vec<u8>,
-    ) -> Result<(), Error> {
-        self.encode_as_type_to(type_id, types, out)
-    }
-}
-
 /// A struct representing a single composite field. To be used in conjunction
 /// with the [`Composite`] struct to construct generic composite shaped types.
 /// this basically takes a type which implements [`EncodeAsType`] and turns it
 /// into something object safe.
 pub struct CompositeField<'a, R> {
-    val: &'a dyn EncodeAsTypeWithResolver<R>,
+    val: &'a dyn DynEncodeAsType<R>,
 }
 
 impl<'a, R> Copy for CompositeField<'a, R> {}
@@ -70,6 +48,12 @@ impl<'a, R: TypeResolver> CompositeField<'a, R> {
         CompositeField { val }
     }
 
+    /// Construct a new composite field given some type which implements
+    /// [`DynEncodeAsType`].
+    pub fn new_dyn(val: &'a dyn DynEncodeAsType<R>) -> Self {
+        CompositeField { val }
+    }
+
     /// SCALE encode this composite field to bytes based on the underlying type.
     pub fn encode_composite_field_to(
         &self,
@@ -82,6 +66,70 @@ impl<'a, R: TypeResolver> CompositeField<'a, R> {
     }
 }
 
+/// This is used by the derive macro to encode fields marked with
+/// `#[encode_as_type(with = "path")]`. It pairs a field with the function that should
+/// be used to encode it, fixing the [`TypeResolver`] up front so that it can be handed
+/// to [`CompositeField::new_dyn`].
+#[doc(hidden)]
+pub struct EncodeFieldWith<'a, T, R: TypeResolver> {
+    value: &'a T,
+    encode_fn: EncodeFn<T, R>,
+}
+
+type EncodeFn<T, R> = fn(&T, <R as TypeResolver>::TypeId, &R, &mut Vec<u8>) -> Result<(), Error>;
+
+impl<'a, T, R: TypeResolver> EncodeFieldWith<'a, T, R> {
+    pub fn new(value: &'a T, encode_fn: EncodeFn<T, R>) -> Self {
+        EncodeFieldWith { value, encode_fn }
+    }
+}
+
+impl<'a, T, R: TypeResolver> DynEncodeAsType<R> for EncodeFieldWith<'a, T, R> {
+    fn encode_as_type_with_resolver_to(
+        &self,
+        type_id: R::TypeId,
+        types: &R,
+        out: &mut Vec<u8>,
+    ) -> Result<(), Error> {
+        (self.encode_fn)(self.value, type_id, types, out)
+    }
+}
+
+/// This trait hands back each of the fields of some value as [`CompositeField`]s, along
+/// with their names if they have any. It's implemented for tuples, and the
+/// [`macro@crate::EncodeAsType`] derive macro implements it for structs. The derive macro
+/// uses it to splice the fields of a value into its parent when the `#[encode_as_type(flatten)]`
+/// attribute is used.
+///
+/// ```rust
+/// use scale_encode::{CompositeField, FlattenFields, TypeResolver};
+///
+/// struct MyType {
+///     foo: bool,
+///     bar: u64,
+/// }
+///
+/// impl FlattenFields for MyType {
+///     fn flatten_fields<'a, R: TypeResolver + 'a>(
+///         &'a self,
+///         out: &mut Vec<(Option<&'a str>, CompositeField<'a, R>)>,
+///     ) -> bool {
+///         out.push((Some("foo"), CompositeField::new(&self.foo)));
+///         out.push((Some("bar"), CompositeField::new(&self.bar)));
+///         false
+///     }
+/// }
+/// ```
+pub trait FlattenFields {
+    /// Push each of the fields of this value, and their names if they have any, to `out`.
+    /// This returns `true` if any fields were left out, in which case the rest can only be
+    /// lined up with the target type by name (see [`Composite::require_named_target()`]).
+    fn flatten_fields<'a, R: TypeResolver + 'a>(
+        &'a self,
+        out: &mut Vec<(Option<&'a str>, CompositeField<'a, R>)>,
+    ) -> bool;
+}
+
 /// This type represents named or unnamed composite values, and can be used to help generate
 /// `EncodeAsType` impls. It's primarily used by the exported macros to do just that.
 ///
@@ -119,6 +167,8 @@ impl<'a, R: TypeResolver> CompositeField<'a, R> {
 /// [`CompositeFields`][CompositeField].
 pub struct Composite<R, Vals> {
     vals: Vals,
+    ignore_trailing_fields: bool,
+    require_named_target: bool,
     marker: core::marker::PhantomData<R>,
 }
 
@@ -142,10 +192,79 @@ where
     pub fn new(vals: Vals) -> Self {
         Composite {
             vals,
+            ignore_trailing_fields: false,
+            require_named_target: false,
             marker: core::marker::PhantomData,
         }
     }
 
+    /// When fields are lined up by position, we normally require that the number of fields
+    /// we have matches the number of fields in the target type. Calling this allows us to
+    /// have more fields than the target type expects, and any trailing fields beyond those
+    /// that the target type expects will be ignored. This mirrors how named fields that aren't
+    /// needed by the target type are ignored.
+    ///
+    /// ```rust
+    /// use scale_encode::{ Composite, CompositeField };
+    /// use scale_info::PortableRegistry;
+    ///
+    /// let m = scale_info::MetaType::new::<(u8, bool)>();
+    /// let mut types = scale_info::Registry::new();
+    /// let type_id = types.register_type(&m).id;
+    /// let types: PortableRegistry = types.into();
+    ///
+    /// let composite = Composite::<PortableRegistry, _>::new([
+    ///     (None, CompositeField::new(&1u64)),
+    ///     (None, CompositeField::new(&true)),
+    ///     (None, CompositeField::new(&"ignored")),
+    /// ].into_iter());
+    ///
+    /// assert!(composite.encode_composite_as_type(type_id, &types).is_err());
+    ///
+    /// let composite = composite.ignore_trailing_fields();
+    /// let bytes = composite.encode_composite_as_type(type_id, &types).unwrap();
+    /// assert_eq!(bytes, vec![1, 1]);
+    /// ```
+    pub fn ignore_trailing_fields(mut self) -> Self {
+        self.ignore_trailing_fields = true;
+        self
+    }
+
+    /// Calling this means that our fields can only be lined up with the target type by name.
+    /// If the target fields are unnamed, then rather than lining our fields up by position,
+    /// an [`ErrorKind::CannotLineUpFieldsByPosition`] error is returned. This is useful when
+    /// some fields may have been left out, since lining up the remaining ones by position
+    /// could quietly encode them into the wrong fields.
+    ///
+    /// ```rust
+    /// use scale_encode::{ Composite, CompositeField };
+    /// use scale_info::PortableRegistry;
+    ///
+    /// let m = scale_info::MetaType::new::<(u8, bool)>();
+    /// let mut types = scale_info::Registry::new();
+    /// let type_id = types.register_type(&m).id;
+    /// let types: PortableRegistry = types.into();
+    ///
+    /// let composite = Composite::<PortableRegistry, _>::new([
+    ///     (Some("a"), CompositeField::new(&1u64)),
+    ///     (Some("c"), CompositeField::new(&true)),
+    /// ].into_iter());
+    ///
+    /// assert!(composite.encode_composite_as_type(type_id, &types).is_ok());
+    ///
+    /// let composite = composite.require_named_target();
+    /// assert!(composite.encode_composite_as_type(type_id, &types).is_err());
+    /// ```
+    pub fn require_named_target(mut self) -> Self {
+        self.require_named_target = true;
+        self
+    }
+
+    // Are there any values in this composite?
+    pub(crate) fn has_values(&self) -> bool {
+        self.vals.len() > 0
+    }
+
     /// A shortcut for [`Self::encode_composite_as_type_to()`] which internally
     /// allocates a [`Vec`] and returns it.
     pub fn encode_composite_as_type(
@@ -261,11 +380,11 @@ where
         let fields = smallvec::SmallVec::<[_; 16]>::from_iter(fields);
 
         // Both the target and source type have to have named fields for us to use
-        // names to line them up.
+        // names to line them up (or we must be told to only line them up by name).
+        let is_target_named = fields.iter().any(|f| f.name.is_some());
         let is_named = {
-            let is_target_named = fields.iter().any(|f| f.name.is_some());
             let is_source_named = vals_iter.clone().any(|(name, _)| name.is_some());
-            is_target_named && is_source_named
+            is_target_named && (is_source_named || self.require_named_target)
         };
 
         if is_named {
@@ -273,6 +392,7 @@ where
             // then encode to the target type by matching the names. If fields are
             // named, we don't even mind if the number of fields doesn't line up;
             // we just ignore any fields we provided that aren't needed.
+            trace_decision!("line up {} fields by name", fields.len());
             let source_fields_by_name: BTreeMap<&str, CompositeField<'a, R>> = vals_iter
                 .map(|(name, val)| (name.unwrap_or(""), val))
                 .collect();
@@ -287,34 +407,52 @@ where
                 };
 
                 // Encode the value to the output:
-                value
-                    .encode_composite_field_to(field.id, types, out)
-                    .map_err(|e| e.at_field(name.to_string()))?;
+                collect::nested(
+                    || value.encode_composite_field_to(field.id, types, out),
+                    |e| e.at_field(name.to_string()),
+                )?;
             }
 
             Ok(())
         } else {
             let fields_len = fields.len();
 
+            // If we can only line up fields by name, then we can't go any further.
+            if self.require_named_target && fields_len > 0 {
+                return Err(Error::new(ErrorKind::CannotLineUpFieldsByPosition));
+            }
+
             // target fields aren't named, so encode by order only. We need the field length
-            // to line up for this to work.
-            if fields_len != vals_iter.len() {
+            // to line up for this to work (unless we're ignoring any trailing fields).
+            let vals_len = vals_iter.len();
+            let is_len_ok = if self.ignore_trailing_fields {
+                vals_len >= fields_len
+            } else {
+                vals_len == fields_len
+            };
+            if !is_len_ok {
                 return Err(Error::new(ErrorKind::WrongLength {
-                    actual_len: vals_iter.len(),
+                    actual_len: vals_len,
                     expected_len: fields_len,
                 }));
             }
+            if vals_len > fields_len {
+                trace_decision!("ignore {} trailing fields", vals_len - fields_len);
+            }
 
+            trace_decision!("line up {fields_len} fields by position");
             for (idx, (field, (name, val))) in fields.iter().zip(vals_iter).enumerate() {
-                val.encode_composite_field_to(field.id.clone(), types, out)
-                    .map_err(|e| {
+                collect::nested(
+                    || val.encode_composite_field_to(field.id.clone(), types, out),
+                    |e| {
                         let loc = if let Some(name) = name {
                             Location::field(name.to_string())
                         } else {
                             Location::idx(idx)
                         };
                         e.at(loc)
-                    })?;
+                    },
+                )?;
             }
             Ok(())
         }
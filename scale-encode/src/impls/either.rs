@@ -0,0 +1,98 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd. (admin@parity.io)
+// This file is a part of the scale-encode crate.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//         http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::{Composite, CompositeField, Variant};
+use crate::{error::Error, EncodeAsType};
+use alloc::vec::Vec;
+use either::Either;
+use scale_type_resolver::TypeResolver;
+
+// Either is encoded into a variant named "Left" or "Right" if one exists. Target types
+// rarely use these names though, and so failing that, we encode into the variant with
+// index 0 (for Left) or 1 (for Right).
+impl<L: EncodeAsType, R: EncodeAsType> EncodeAsType for Either<L, R> {
+    fn encode_as_type_to<Resolver: TypeResolver>(
+        &self,
+        type_id: Resolver::TypeId,
+        types: &Resolver,
+        out: &mut Vec<u8>,
+    ) -> Result<(), Error> {
+        let (name, index, field) = match self {
+            Either::Left(l) => ("Left", 0, CompositeField::new(l)),
+            Either::Right(r) => ("Right", 1, CompositeField::new(r)),
+        };
+        Variant {
+            name,
+            fields: Composite::new([(None, field)].iter().copied()),
+        }
+        .encode_variant_as_type_with_fallback_to(type_id, types, &[], Some(index), out)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::error::ErrorKind;
+    use crate::testing::encode_type;
+    use alloc::string::String;
+    use codec::{Decode, Encode};
+    use scale_info::TypeInfo;
+
+    #[test]
+    fn either_encodes_by_name() {
+        #[derive(Encode, Decode, TypeInfo)]
+        enum Target {
+            #[codec(index = 3)]
+            Right(u64),
+            #[codec(index = 1)]
+            Left(bool),
+        }
+
+        assert_eq!(
+            encode_type::<_, Target>(&Either::<bool, u8>::Left(true)).unwrap(),
+            Target::Left(true).encode()
+        );
+        assert_eq!(
+            encode_type::<_, Target>(&Either::<bool, u8>::Right(2)).unwrap(),
+            Target::Right(2).encode()
+        );
+    }
+
+    #[test]
+    fn either_falls_back_to_index() {
+        #[derive(Encode, Decode, TypeInfo)]
+        enum Target {
+            Ok(u64),
+            Err(String),
+        }
+
+        assert_eq!(
+            encode_type::<_, Target>(&Either::<u8, &str>::Left(1)).unwrap(),
+            Target::Ok(1).encode()
+        );
+        assert_eq!(
+            encode_type::<_, Target>(&Either::<u8, &str>::Right("hi")).unwrap(),
+            Target::Err("hi".into()).encode()
+        );
+
+        // Without a matching name or index, we fail as usual:
+        #[derive(Encode, Decode, TypeInfo)]
+        enum OneVariant {
+            Foo(u64),
+        }
+        let err = encode_type::<_, OneVariant>(&Either::<u8, u8>::Right(1)).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::CannotFindVariant { .. }));
+    }
+}
@@ -0,0 +1,72 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd. (admin@parity.io)
+// This file is a part of the scale-encode crate.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//         http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{error::Error, EncodeAsType};
+use alloc::vec::Vec;
+use fixed::{
+    types::extra::{LeEqU128, LeEqU16, LeEqU32, LeEqU64, LeEqU8},
+    FixedI128, FixedI16, FixedI32, FixedI64, FixedI8, FixedU128, FixedU16, FixedU32, FixedU64,
+    FixedU8,
+};
+use scale_type_resolver::TypeResolver;
+
+// Fixed point numbers are encoded as the integer that they are backed by.
+macro_rules! impl_encode {
+    ($($ty:ident: $frac_bound:ident),*) => {$(
+        impl<Frac: $frac_bound> EncodeAsType for $ty<Frac> {
+            fn encode_as_type_to<R: TypeResolver>(
+                &self,
+                type_id: R::TypeId,
+                types: &R,
+                out: &mut Vec<u8>,
+            ) -> Result<(), Error> {
+                self.to_bits().encode_as_type_to(type_id, types, out)
+            }
+        }
+    )*}
+}
+impl_encode!(
+    FixedU8: LeEqU8,
+    FixedU16: LeEqU16,
+    FixedU32: LeEqU32,
+    FixedU64: LeEqU64,
+    FixedU128: LeEqU128,
+    FixedI8: LeEqU8,
+    FixedI16: LeEqU16,
+    FixedI32: LeEqU32,
+    FixedI64: LeEqU64,
+    FixedI128: LeEqU128
+);
+
+#[cfg(test)]
+mod test {
+    use crate::testing::encode_type;
+    use codec::Encode;
+    use fixed::types::{I32F32, U48F16};
+
+    #[test]
+    fn fixed_point_numbers_encode_as_their_bits() {
+        let n = U48F16::from_num(1.5);
+        assert_eq!(encode_type::<_, u64>(&n).unwrap(), n.to_bits().encode());
+        assert_eq!(encode_type::<_, u64>(&n).unwrap(), (3u64 << 15).encode());
+
+        let n = I32F32::from_num(-2.25);
+        assert_eq!(encode_type::<_, i64>(&n).unwrap(), n.to_bits().encode());
+
+        // The usual numeric range checks apply to the bits:
+        assert!(encode_type::<_, u8>(&U48F16::from_num(1)).is_err());
+        assert!(encode_type::<_, u64>(&I32F32::from_num(-1)).is_err());
+    }
+}
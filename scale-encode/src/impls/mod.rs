@@ -15,14 +15,24 @@
 
 #[cfg(feature = "bits")]
 mod bits;
+mod bytes;
+mod compat;
 mod composite;
+#[cfg(feature = "either")]
+mod either;
+#[cfg(feature = "fixed")]
+mod fixed;
+mod pre_encoded;
 #[cfg(feature = "primitive-types")]
 mod primitive_types;
+mod sequence;
+#[cfg(feature = "std")]
+mod sync;
 mod variant;
 
 use crate::{
-    error::{Error, ErrorKind, Kind},
-    EncodeAsFields, EncodeAsType,
+    error::{collect, Error, ErrorKind, Kind},
+    DynEncodeAsFields, DynEncodeAsType, EncodeAsFields, EncodeAsType,
 };
 use alloc::{
     borrow::ToOwned,
@@ -36,19 +46,30 @@ use alloc::{
 };
 use codec::{Compact, Encode};
 use core::{
-    marker::PhantomData,
+    cell::{Ref, RefMut},
+    marker::{PhantomData, PhantomPinned},
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
     num::{
         NonZeroI128, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI8, NonZeroU128, NonZeroU16,
-        NonZeroU32, NonZeroU64, NonZeroU8,
+        NonZeroU32, NonZeroU64, NonZeroU8, Saturating, Wrapping,
     },
-    ops::{Range, RangeInclusive},
+    ops::{Deref, Range, RangeFrom, RangeInclusive, RangeTo},
+    pin::Pin,
     time::Duration,
 };
 use scale_type_resolver::{visitor, FieldIter, Primitive, ResolvedTypeVisitor, TypeResolver};
+#[cfg(feature = "std")]
+use std::collections::{HashMap, HashSet};
 
 // Useful to help encode key-value types or custom variant types manually.
 // Primarily used in the derive macro.
-pub use composite::{Composite, CompositeField};
+pub use bytes::{AsBytes, Hex};
+#[cfg(feature = "base64")]
+pub use bytes::{Base64, Base64Alphabet};
+pub use compat::{ByKeyOrder, NumberAsBool, OkOnly, OptionAsSeq, Seq, Tagged, WithContext};
+pub use composite::{Composite, CompositeField, EncodeFieldWith, FlattenFields};
+pub use pre_encoded::{CheckedPreEncoded, Codec, PreEncoded};
+pub use sequence::SequenceTarget;
 pub use variant::Variant;
 
 fn resolve_type_and_encode<
@@ -60,6 +81,12 @@ fn resolve_type_and_encode<
     type_id: R::TypeId,
     visitor: V,
 ) -> Result<(), Error> {
+    #[cfg(feature = "trace")]
+    let visitor = crate::trace::TracingVisitor {
+        type_id: type_id.clone(),
+        inner: visitor,
+    };
+
     match types.resolve_type(type_id, visitor) {
         Ok(res) => res,
         Err(e) => Err(Error::new(ErrorKind::TypeResolvingError(e.to_string()))),
@@ -115,24 +142,57 @@ impl EncodeAsType for str {
             })
         };
 
-        let v = visitor::new(type_id.clone(), |type_id, _| Err(wrong_shape_err(type_id)))
-            .visit_primitive(|type_id, primitive| {
-                if primitive == Primitive::Str {
-                    self.encode_to(out);
-                    Ok(())
-                } else {
-                    Err(wrong_shape_err(type_id))
-                }
-            })
-            .visit_not_found(|type_id| {
-                Err(Error::new(ErrorKind::TypeNotFound(format!("{type_id:?}"))))
-            });
+        // Strings can also be encoded into sequences or arrays of chars.
+        let is_char = |type_id: R::TypeId| {
+            let type_id = find_single_entry_with_same_repr(type_id, types);
+            let v = visitor::new((), |_, _| false).visit_primitive(|_, p| p == Primitive::Char);
+            types.resolve_type(type_id, v).unwrap_or(false)
+        };
+
+        let v = visitor::new((type_id.clone(), out), |(type_id, _), _| {
+            Err(wrong_shape_err(type_id))
+        })
+        .visit_primitive(|(type_id, out), primitive| {
+            if primitive == Primitive::Str {
+                self.encode_to(out);
+                Ok(())
+            } else {
+                Err(wrong_shape_err(type_id))
+            }
+        })
+        .visit_sequence(|(type_id, out), _, inner_type_id| {
+            if is_char(inner_type_id) {
+                trace_decision!("encode string as a sequence of chars");
+                encode_iterable_sequence_to(self.chars().count(), self.chars(), type_id, types, out)
+            } else {
+                Err(wrong_shape_err(type_id))
+            }
+        })
+        .visit_array(|(type_id, out), inner_type_id, _| {
+            if is_char(inner_type_id) {
+                trace_decision!("encode string as an array of chars");
+                encode_iterable_sequence_to(self.chars().count(), self.chars(), type_id, types, out)
+            } else {
+                Err(wrong_shape_err(type_id))
+            }
+        })
+        .visit_variant(|(type_id, _), _, _| {
+            Err(Error::new(ErrorKind::WrongShapeWithHint {
+                actual: Kind::Str,
+                expected_id: format!("{type_id:?}"),
+                hint: "strings cannot select variants by name, but scale_encode::Variant can"
+                    .into(),
+            }))
+        })
+        .visit_not_found(|(type_id, _)| {
+            Err(Error::new(ErrorKind::TypeNotFound(format!("{type_id:?}"))))
+        });
 
         resolve_type_and_encode(types, type_id, v)
     }
 }
 
-impl<'a, T> EncodeAsType for &'a T
+impl<T> EncodeAsType for &T
 where
     T: EncodeAsType + ?Sized,
 {
@@ -160,6 +220,35 @@ where
     }
 }
 
+// Values borrowed from a RefCell are encoded like the values themselves.
+impl<T> EncodeAsType for Ref<'_, T>
+where
+    T: EncodeAsType + ?Sized,
+{
+    fn encode_as_type_to<R: TypeResolver>(
+        &self,
+        type_id: R::TypeId,
+        types: &R,
+        out: &mut Vec<u8>,
+    ) -> Result<(), Error> {
+        (**self).encode_as_type_to(type_id, types, out)
+    }
+}
+
+impl<T> EncodeAsType for RefMut<'_, T>
+where
+    T: EncodeAsType + ?Sized,
+{
+    fn encode_as_type_to<R: TypeResolver>(
+        &self,
+        type_id: R::TypeId,
+        types: &R,
+        out: &mut Vec<u8>,
+    ) -> Result<(), Error> {
+        (**self).encode_as_type_to(type_id, types, out)
+    }
+}
+
 impl<T> EncodeAsType for [T]
 where
     T: EncodeAsType,
@@ -170,7 +259,11 @@ where
         types: &R,
         out: &mut Vec<u8>,
     ) -> Result<(), Error> {
-        encode_iterable_sequence_to(self.len(), self.iter(), type_id, types, out)
+        if let Some((primitive, encode_scalars)) = T::__scalar_encoding() {
+            encode_scalars_to(self, primitive, encode_scalars, type_id, types, out)
+        } else {
+            encode_iterable_sequence_to(self.len(), self.iter(), type_id, types, out)
+        }
     }
 }
 
@@ -240,10 +333,119 @@ impl<T: EncodeAsType> EncodeAsType for Option<T> {
     }
 }
 
+// Ranges are encoded as just their start and end values, either like a tuple or into a
+// sequence or array of two values. Notably, this means that whether the end of the range
+// is inclusive or exclusive is not preserved in the encoded bytes.
+macro_rules! impl_encode_range {
+    ($ty:ident where |$val:ident| $start:expr, $end:expr) => {
+        impl<T: EncodeAsType> EncodeAsType for $ty<T> {
+            fn encode_as_type_to<R: TypeResolver>(
+                &self,
+                type_id: R::TypeId,
+                types: &R,
+                out: &mut Vec<u8>,
+            ) -> Result<(), Error> {
+                let $val = self;
+                let (start, end) = ($start, $end);
+                let v = visitor::new((type_id.clone(), out), |(type_id, out), _| {
+                    (start, end).encode_as_type_to(type_id, types, out)
+                })
+                .visit_array(|(type_id, out), _, _| {
+                    encode_iterable_sequence_to(2, [start, end].into_iter(), type_id, types, out)
+                })
+                .visit_sequence(|(type_id, out), _, _| {
+                    encode_iterable_sequence_to(2, [start, end].into_iter(), type_id, types, out)
+                });
+
+                resolve_type_and_encode(types, type_id, v)
+            }
+        }
+    };
+}
+impl_encode_range!(Range where |val| &val.start, &val.end);
+impl_encode_range!(RangeInclusive where |val| val.start(), val.end());
+
+// Encode an IPv4 address to its octets.
+impl EncodeAsType for Ipv4Addr {
+    fn encode_as_type_to<R: TypeResolver>(
+        &self,
+        type_id: R::TypeId,
+        types: &R,
+        out: &mut Vec<u8>,
+    ) -> Result<(), Error> {
+        self.octets().encode_as_type_to(type_id, types, out)
+    }
+}
+
+// IP addresses are encoded into a variant named "V4" or "V6" if one exists, like
+// std's IpAddr. Failing that, we encode into the variant with index 0 (for V4) or
+// 1 (for V6), so that enums which name these variants differently still work.
+impl EncodeAsType for IpAddr {
+    fn encode_as_type_to<R: TypeResolver>(
+        &self,
+        type_id: R::TypeId,
+        types: &R,
+        out: &mut Vec<u8>,
+    ) -> Result<(), Error> {
+        let (name, index, field) = match self {
+            IpAddr::V4(addr) => ("V4", 0, CompositeField::new(addr)),
+            IpAddr::V6(addr) => ("V6", 1, CompositeField::new(addr)),
+        };
+        Variant {
+            name,
+            fields: Composite::new([(None, field)].iter().copied()),
+        }
+        .encode_variant_as_type_with_fallback_to(type_id, types, &[], Some(index), out)
+    }
+}
+
+// Encode an IPv6 address to its octets by default, but to its segments if the
+// target is an array of 8 values, so that we don't silently produce the wrong bytes.
+impl EncodeAsType for Ipv6Addr {
+    fn encode_as_type_to<R: TypeResolver>(
+        &self,
+        type_id: R::TypeId,
+        types: &R,
+        out: &mut Vec<u8>,
+    ) -> Result<(), Error> {
+        let type_id = find_single_entry_with_same_repr(type_id, types);
+
+        let v = visitor::new((type_id.clone(), out), |(type_id, out), _| {
+            self.octets().encode_as_type_to(type_id, types, out)
+        })
+        .visit_array(|(type_id, out), _, len| {
+            if len == 8 {
+                self.segments().encode_as_type_to(type_id, types, out)
+            } else {
+                self.octets().encode_as_type_to(type_id, types, out)
+            }
+        });
+
+        resolve_type_and_encode(types, type_id, v)
+    }
+}
+
 // Encode any numeric type implementing ToNumber, above, into the type ID given.
 macro_rules! impl_encode_number {
-    ($ty:ty) => {
+    ($ty:ty => $primitive:ident) => {
+        impl_encode_number!($ty => $primitive as |slice, out| {
+            out.reserve(core::mem::size_of_val(slice));
+            for n in slice {
+                n.encode_to(out);
+            }
+        });
+    };
+    ($ty:ty $(=> $primitive:ident as |$scalars:ident, $scalars_out:ident| $encode_scalars:expr)?) => {
         impl EncodeAsType for $ty {
+            $(
+                fn __scalar_encoding() -> Option<crate::ScalarEncoding<Self>> {
+                    fn encode_scalars($scalars: &[$ty], $scalars_out: &mut Vec<u8>) {
+                        $encode_scalars
+                    }
+                    Some((Primitive::$primitive, encode_scalars))
+                }
+            )?
+
             fn encode_as_type_to<R: TypeResolver>(
                 &self,
                 type_id: R::TypeId,
@@ -272,6 +474,8 @@ macro_rules! impl_encode_number {
                                     expected_id: format!("{target_id:?}"),
                                 })
                             })?;
+                            #[cfg(feature = "trace")]
+                            crate::trace::record(crate::trace::number_decision::<$ty, T>);
                             n.encode_to(out);
                             Ok(())
                         }
@@ -287,6 +491,18 @@ macro_rules! impl_encode_number {
                             Primitive::I32 => try_num::<i32>(*self, type_id, out),
                             Primitive::I64 => try_num::<i64>(*self, type_id, out),
                             Primitive::I128 => try_num::<i128>(*self, type_id, out),
+                            Primitive::Char => {
+                                // Chars are encoded like u32s, but must be valid unicode scalar values.
+                                let c = u32::try_from(*self).ok().and_then(char::from_u32).ok_or_else(|| {
+                                    Error::new(ErrorKind::NumberOutOfRange {
+                                        value: self.to_string(),
+                                        expected_id: format!("{type_id:?}"),
+                                    })
+                                })?;
+                                trace_decision!("encode {} as char {c:?}", stringify!($ty));
+                                (c as u32).encode_to(out);
+                                Ok(())
+                            }
                             _ => Err(wrong_shape_err(type_id)),
                         }
                     })
@@ -301,6 +517,7 @@ macro_rules! impl_encode_number {
                                         expected_id: format!("{:?}", $inner_type_id),
                                     })
                                 })?;
+                                trace_decision!("encode {} as compact {}", stringify!($ty), stringify!($type));
                                 Compact(n).encode_to($out);
                                 Ok(())
                             }};
@@ -338,17 +555,17 @@ macro_rules! impl_encode_number {
         }
     };
 }
-impl_encode_number!(u8);
-impl_encode_number!(u16);
-impl_encode_number!(u32);
-impl_encode_number!(u64);
-impl_encode_number!(u128);
+impl_encode_number!(u8 => U8 as |slice, out| out.extend_from_slice(slice));
+impl_encode_number!(u16 => U16);
+impl_encode_number!(u32 => U32);
+impl_encode_number!(u64 => U64);
+impl_encode_number!(u128 => U128);
 impl_encode_number!(usize);
-impl_encode_number!(i8);
-impl_encode_number!(i16);
-impl_encode_number!(i32);
-impl_encode_number!(i64);
-impl_encode_number!(i128);
+impl_encode_number!(i8 => I8);
+impl_encode_number!(i16 => I16);
+impl_encode_number!(i32 => I32);
+impl_encode_number!(i64 => I64);
+impl_encode_number!(i128 => I128);
 impl_encode_number!(isize);
 
 // Encode tuple types to any matching type.
@@ -364,11 +581,43 @@ macro_rules! impl_encode_tuple {
                 ].iter().copied()).encode_composite_as_type_to(type_id, types, out)
             }
         }
+        impl < $($t),* > FlattenFields for ($($t,)*) where $($t: EncodeAsType),* {
+            fn flatten_fields<'a, Resolver: TypeResolver + 'a>(&'a self, out: &mut Vec<(Option<&'a str>, CompositeField<'a, Resolver>)>) -> bool {
+                let ($($name,)*) = self;
+                $(
+                    out.push((None, CompositeField::new($name)));
+                )*
+                false
+            }
+        }
+    }
+}
+// The empty tuple encodes like an empty composite, but can also
+// be encoded into empty sequences and zero length arrays.
+impl EncodeAsType for () {
+    fn encode_as_type_to<R: TypeResolver>(
+        &self,
+        type_id: R::TypeId,
+        types: &R,
+        out: &mut Vec<u8>,
+    ) -> Result<(), Error> {
+        let v = visitor::new((type_id.clone(), out), |(type_id, out), _| {
+            Composite::new(core::iter::empty::<(Option<&str>, CompositeField<R>)>())
+                .encode_composite_as_type_to(type_id, types, out)
+        })
+        .visit_array(|(type_id, out), _, _| {
+            encode_iterable_sequence_to(0, core::iter::empty::<()>(), type_id, types, out)
+        })
+        .visit_sequence(|(type_id, out), _, _| {
+            encode_iterable_sequence_to(0, core::iter::empty::<()>(), type_id, types, out)
+        });
+
+        resolve_type_and_encode(types, type_id, v)
     }
 }
+
 #[rustfmt::skip]
 const _: () = {
-    impl_encode_tuple!();
     impl_encode_tuple!(a: A);
     impl_encode_tuple!(a: A, b: B);
     impl_encode_tuple!(a: A, b: B, c: C);
@@ -388,7 +637,20 @@ const _: () = {
     impl_encode_tuple!(a: A, b: B, c: C, d: D, e: E, f: F, g: G, h: H, i: I, j: J, k: K, l: L, m: M, n: N, o: O, p: P, q: Q);
     impl_encode_tuple!(a: A, b: B, c: C, d: D, e: E, f: F, g: G, h: H, i: I, j: J, k: K, l: L, m: M, n: N, o: O, p: P, q: Q, r: R);
     impl_encode_tuple!(a: A, b: B, c: C, d: D, e: E, f: F, g: G, h: H, i: I, j: J, k: K, l: L, m: M, n: N, o: O, p: P, q: Q, r: R, s: S);
-    // ^ Note: We make sure to support as many as parity-scale-codec's Encode impls do.
+    impl_encode_tuple!(a: A, b: B, c: C, d: D, e: E, f: F, g: G, h: H, i: I, j: J, k: K, l: L, m: M, n: N, o: O, p: P, q: Q, r: R, s: S, t: T);
+    impl_encode_tuple!(a: A, b: B, c: C, d: D, e: E, f: F, g: G, h: H, i: I, j: J, k: K, l: L, m: M, n: N, o: O, p: P, q: Q, r: R, s: S, t: T, u: U);
+    impl_encode_tuple!(a: A, b: B, c: C, d: D, e: E, f: F, g: G, h: H, i: I, j: J, k: K, l: L, m: M, n: N, o: O, p: P, q: Q, r: R, s: S, t: T, u: U, v: V);
+    impl_encode_tuple!(a: A, b: B, c: C, d: D, e: E, f: F, g: G, h: H, i: I, j: J, k: K, l: L, m: M, n: N, o: O, p: P, q: Q, r: R, s: S, t: T, u: U, v: V, w: W);
+    impl_encode_tuple!(a: A, b: B, c: C, d: D, e: E, f: F, g: G, h: H, i: I, j: J, k: K, l: L, m: M, n: N, o: O, p: P, q: Q, r: R, s: S, t: T, u: U, v: V, w: W, x: X);
+    impl_encode_tuple!(a: A, b: B, c: C, d: D, e: E, f: F, g: G, h: H, i: I, j: J, k: K, l: L, m: M, n: N, o: O, p: P, q: Q, r: R, s: S, t: T, u: U, v: V, w: W, x: X, y: Y);
+    impl_encode_tuple!(a: A, b: B, c: C, d: D, e: E, f: F, g: G, h: H, i: I, j: J, k: K, l: L, m: M, n: N, o: O, p: P, q: Q, r: R, s: S, t: T, u: U, v: V, w: W, x: X, y: Y, z: Z);
+    impl_encode_tuple!(a: A, b: B, c: C, d: D, e: E, f: F, g: G, h: H, i: I, j: J, k: K, l: L, m: M, n: N, o: O, p: P, q: Q, r: R, s: S, t: T, u: U, v: V, w: W, x: X, y: Y, z: Z, aa: AA);
+    impl_encode_tuple!(a: A, b: B, c: C, d: D, e: E, f: F, g: G, h: H, i: I, j: J, k: K, l: L, m: M, n: N, o: O, p: P, q: Q, r: R, s: S, t: T, u: U, v: V, w: W, x: X, y: Y, z: Z, aa: AA, ab: AB);
+    impl_encode_tuple!(a: A, b: B, c: C, d: D, e: E, f: F, g: G, h: H, i: I, j: J, k: K, l: L, m: M, n: N, o: O, p: P, q: Q, r: R, s: S, t: T, u: U, v: V, w: W, x: X, y: Y, z: Z, aa: AA, ab: AB, ac: AC);
+    impl_encode_tuple!(a: A, b: B, c: C, d: D, e: E, f: F, g: G, h: H, i: I, j: J, k: K, l: L, m: M, n: N, o: O, p: P, q: Q, r: R, s: S, t: T, u: U, v: V, w: W, x: X, y: Y, z: Z, aa: AA, ab: AB, ac: AC, ad: AD);
+    impl_encode_tuple!(a: A, b: B, c: C, d: D, e: E, f: F, g: G, h: H, i: I, j: J, k: K, l: L, m: M, n: N, o: O, p: P, q: Q, r: R, s: S, t: T, u: U, v: V, w: W, x: X, y: Y, z: Z, aa: AA, ab: AB, ac: AC, ad: AD, ae: AE);
+    impl_encode_tuple!(a: A, b: B, c: C, d: D, e: E, f: F, g: G, h: H, i: I, j: J, k: K, l: L, m: M, n: N, o: O, p: P, q: Q, r: R, s: S, t: T, u: U, v: V, w: W, x: X, y: Y, z: Z, aa: AA, ab: AB, ac: AC, ad: AD, ae: AE, af: AF);
+    // ^ Note: We make sure to support at least as many as parity-scale-codec's Encode impls do.
 };
 
 // Implement encoding via iterators for ordered collections
@@ -408,11 +670,63 @@ macro_rules! impl_encode_seq_via_iterator {
         }
     }
 }
-impl_encode_seq_via_iterator!(BTreeSet[K]);
 impl_encode_seq_via_iterator!(LinkedList[V]);
 impl_encode_seq_via_iterator!(BinaryHeap[V]);
 impl_encode_seq_via_iterator!(VecDeque[V]);
-impl_encode_seq_via_iterator!(Vec[V]);
+
+// Sets are encoded like sequences (in ascending order), but we give a more helpful
+// error if somebody tries encoding one into a struct with named fields.
+impl<K: EncodeAsType> EncodeAsType for BTreeSet<K> {
+    fn encode_as_type_to<R: TypeResolver>(
+        &self,
+        type_id: R::TypeId,
+        types: &R,
+        out: &mut Vec<u8>,
+    ) -> Result<(), Error> {
+        let v = visitor::new((type_id.clone(), out), |(type_id, out), _| {
+            encode_iterable_sequence_to(self.len(), self.iter(), type_id, types, out)
+        })
+        .visit_composite(|(type_id, out), _, mut fields| {
+            let is_named = fields.len() > 1 && Iterator::any(&mut fields, |f| f.name.is_some());
+            if is_named {
+                return Err(Error::new(ErrorKind::WrongShapeWithHint {
+                    actual: Kind::Array,
+                    expected_id: format!("{type_id:?}"),
+                    hint: "a set cannot be encoded as a named struct, since its values have no field names".into(),
+                }));
+            }
+            encode_iterable_sequence_to(self.len(), self.iter(), type_id, types, out)
+        });
+
+        resolve_type_and_encode(types, type_id, v)
+    }
+}
+
+// Hash sets have no stable iteration order, so their values are sorted (by collecting
+// them into a BTreeSet) so that they're encoded exactly as a BTreeSet would be.
+#[cfg(feature = "std")]
+impl<K: EncodeAsType + Ord, S> EncodeAsType for HashSet<K, S> {
+    fn encode_as_type_to<R: TypeResolver>(
+        &self,
+        type_id: R::TypeId,
+        types: &R,
+        out: &mut Vec<u8>,
+    ) -> Result<(), Error> {
+        let values: BTreeSet<&K> = self.iter().collect();
+        values.encode_as_type_to(type_id, types, out)
+    }
+}
+
+impl<T: EncodeAsType> EncodeAsType for Vec<T> {
+    fn encode_as_type_to<R: TypeResolver>(
+        &self,
+        type_id: R::TypeId,
+        types: &R,
+        out: &mut Vec<u8>,
+    ) -> Result<(), Error> {
+        self.as_slice().encode_as_type_to(type_id, types, out)
+    }
+}
 
 impl<K: AsRef<str>, V: EncodeAsType> EncodeAsType for BTreeMap<K, V> {
     fn encode_as_type_to<R: TypeResolver>(
@@ -433,6 +747,13 @@ impl<K: AsRef<str>, V: EncodeAsType> EncodeAsType for BTreeMap<K, V> {
         })
         .visit_sequence(|(type_id, out), _, _| {
             encode_iterable_sequence_to(self.len(), self.values(), type_id, types, out)
+        })
+        .visit_variant(|(type_id, _), _, _| {
+            Err(Error::new(ErrorKind::WrongShapeWithHint {
+                actual: Kind::Struct,
+                expected_id: format!("{type_id:?}"),
+                hint: "a map cannot select an enum variant directly; encode a scale_encode::Variant which names the variant and uses the map entries as its fields instead".into(),
+            }))
         });
 
         resolve_type_and_encode(types, type_id, v)
@@ -453,9 +774,142 @@ impl<K: AsRef<str>, V: EncodeAsType> EncodeAsFields for BTreeMap<K, V> {
     }
 }
 
+// Hash maps have no stable iteration order, so we sort their entries by key (in the same way
+// that a BTreeMap would order them) before encoding, so that the output is deterministic.
+#[cfg(feature = "std")]
+fn sorted_hash_map_entries<K: AsRef<str> + Ord, V, S>(map: &HashMap<K, V, S>) -> Vec<(&str, &V)> {
+    let mut entries: Vec<_> = map.iter().collect();
+    entries.sort_unstable_by_key(|(k, _)| *k);
+    entries.into_iter().map(|(k, v)| (k.as_ref(), v)).collect()
+}
+
+#[cfg(feature = "std")]
+impl<K: AsRef<str> + Ord, V: EncodeAsType, S> EncodeAsType for HashMap<K, V, S> {
+    fn encode_as_type_to<R: TypeResolver>(
+        &self,
+        type_id: R::TypeId,
+        types: &R,
+        out: &mut Vec<u8>,
+    ) -> Result<(), Error> {
+        let entries = sorted_hash_map_entries(self);
+        let values = || entries.iter().map(|(_, v)| *v);
+
+        let v = visitor::new((type_id.clone(), out), |(type_id, out), _| {
+            Composite::new(
+                entries
+                    .iter()
+                    .map(|(k, v)| (Some(*k), CompositeField::new(*v))),
+            )
+            .encode_composite_as_type_to(type_id, types, out)
+        })
+        .visit_array(|(type_id, out), _, _| {
+            encode_iterable_sequence_to(entries.len(), values(), type_id, types, out)
+        })
+        .visit_sequence(|(type_id, out), _, _| {
+            encode_iterable_sequence_to(entries.len(), values(), type_id, types, out)
+        })
+        .visit_variant(|(type_id, _), _, _| {
+            Err(Error::new(ErrorKind::WrongShapeWithHint {
+                actual: Kind::Struct,
+                expected_id: format!("{type_id:?}"),
+                hint: "a map cannot select an enum variant directly; encode a scale_encode::Variant which names the variant and uses the map entries as its fields instead".into(),
+            }))
+        });
+
+        resolve_type_and_encode(types, type_id, v)
+    }
+}
+#[cfg(feature = "std")]
+impl<K: AsRef<str> + Ord, V: EncodeAsType, S> EncodeAsFields for HashMap<K, V, S> {
+    fn encode_as_fields_to<R: TypeResolver>(
+        &self,
+        fields: &mut dyn FieldIter<'_, R::TypeId>,
+        types: &R,
+        out: &mut Vec<u8>,
+    ) -> Result<(), Error> {
+        Composite::new(
+            sorted_hash_map_entries(self)
+                .into_iter()
+                .map(|(k, v)| (Some(k), CompositeField::new(v))),
+        )
+        .encode_composite_fields_to(fields, types, out)
+    }
+}
+
+// Slices of erased values encode much like tuples, except that each value can be of a
+// different type. Sequences and arrays are also supported, since each value is encoded
+// to the same element type.
+impl<R: TypeResolver> DynEncodeAsType<R> for [&dyn DynEncodeAsType<R>] {
+    fn encode_as_type_with_resolver_to(
+        &self,
+        type_id: R::TypeId,
+        types: &R,
+        out: &mut Vec<u8>,
+    ) -> Result<(), Error> {
+        let v = visitor::new((type_id.clone(), out), |(type_id, out), _| {
+            Composite::new(self.iter().map(|v| (None, CompositeField::new_dyn(*v))))
+                .encode_composite_as_type_to(type_id, types, out)
+        })
+        .visit_array(|(_, out), inner_ty_id: R::TypeId, array_len| {
+            if array_len != self.len() {
+                return Err(Error::new(ErrorKind::WrongLength {
+                    actual_len: self.len(),
+                    expected_len: array_len,
+                }));
+            }
+            for (idx, item) in self.iter().enumerate() {
+                collect::nested(
+                    || item.encode_as_type_with_resolver_to(inner_ty_id.clone(), types, out),
+                    |e| e.at_idx(idx),
+                )?;
+            }
+            Ok(())
+        })
+        .visit_sequence(|(_, out), _, inner_ty_id| {
+            Compact(self.len() as u32).encode_to(out);
+            for (idx, item) in self.iter().enumerate() {
+                collect::nested(
+                    || item.encode_as_type_with_resolver_to(inner_ty_id.clone(), types, out),
+                    |e| e.at_idx(idx),
+                )?;
+            }
+            Ok(())
+        });
+
+        resolve_type_and_encode(types, type_id, v)
+    }
+}
+impl<R: TypeResolver> DynEncodeAsFields<R> for [&dyn DynEncodeAsType<R>] {
+    fn encode_as_fields_with_resolver_to(
+        &self,
+        fields: &mut dyn FieldIter<'_, R::TypeId>,
+        types: &R,
+        out: &mut Vec<u8>,
+    ) -> Result<(), Error> {
+        Composite::new(self.iter().map(|v| (None, CompositeField::new_dyn(*v))))
+            .encode_composite_fields_to(fields, types, out)
+    }
+}
+
 // Generate EncodeAsType impls for simple types that can be easily transformed
 // into types we have impls for already.
 macro_rules! impl_encode_like {
+    ($ty:ident <$param:ident: ?Sized> as $delegate_ty:ty where |$val:ident| $expr:expr) => {
+        impl <$param: EncodeAsType + ?Sized> EncodeAsType for $ty <$param> {
+            fn encode_as_type_to<R: TypeResolver>(
+                &self,
+                type_id: R::TypeId,
+                types: &R,
+                out: &mut Vec<u8>,
+            ) -> Result<(), Error> {
+                let delegate: $delegate_ty = {
+                    let $val = self;
+                    $expr
+                };
+                delegate.encode_as_type_to(type_id, types, out)
+            }
+        }
+    };
     ($ty:ident $(<$( $param:ident ),+>)? as $delegate_ty:ty where |$val:ident| $expr:expr) => {
         impl $(< $($param: EncodeAsType),+ >)? EncodeAsType for $ty $(<$( $param ),+>)? {
             fn encode_as_type_to<R: TypeResolver>(
@@ -474,9 +928,11 @@ macro_rules! impl_encode_like {
     }
 }
 impl_encode_like!(String as &str where |val| val);
-impl_encode_like!(Box<T> as &T where |val| val);
-impl_encode_like!(Arc<T> as &T where |val| val);
-impl_encode_like!(Rc<T> as &T where |val| val);
+// Unsized types like `Arc<[u8]>` delegate to the underlying slice, which means that
+// they benefit from the same fast paths as slices do.
+impl_encode_like!(Box<T: ?Sized> as &T where |val| val);
+impl_encode_like!(Arc<T: ?Sized> as &T where |val| val);
+impl_encode_like!(Rc<T: ?Sized> as &T where |val| val);
 impl_encode_like!(char as u32 where |val| *val as u32);
 impl_encode_like!(NonZeroU8 as u8 where |val| val.get());
 impl_encode_like!(NonZeroU16 as u16 where |val| val.get());
@@ -489,9 +945,13 @@ impl_encode_like!(NonZeroI32 as i32 where |val| val.get());
 impl_encode_like!(NonZeroI64 as i64 where |val| val.get());
 impl_encode_like!(NonZeroI128 as i128 where |val| val.get());
 impl_encode_like!(Duration as (u64, u32) where |val| (val.as_secs(), val.subsec_nanos()));
-impl_encode_like!(Range<T> as (&T, &T) where |val| (&val.start, &val.end));
-impl_encode_like!(RangeInclusive<T> as (&T, &T) where |val| ((val.start()), (val.end())));
+// Half-open ranges only carry one meaningful value, and so are encoded as just that value.
+impl_encode_like!(RangeFrom<T> as &T where |val| &val.start);
+impl_encode_like!(RangeTo<T> as &T where |val| &val.end);
 impl_encode_like!(Compact<T> as &T where |val| &val.0);
+impl_encode_like!(Wrapping<T> as &T where |val| &val.0);
+impl_encode_like!(Saturating<T> as &T where |val| &val.0);
+impl_encode_like!(PhantomPinned as () where |_val| ());
 
 // Generate EncodeAsField impls for common smart pointers containing
 // types we have impls for already.
@@ -513,13 +973,59 @@ impl_encode_like_to_fields!(Box<T> as &T where |val| val);
 impl_encode_like_to_fields!(Rc<T> as &T where |val| val);
 impl_encode_like_to_fields!(Arc<T> as &T where |val| val);
 
-// Attempt to recurse into some type, returning the innermost type found that has an identical
-// SCALE encoded representation to the given type. For instance, `(T,)` encodes identically to
-// `T`, as does `Mytype { inner: T }` or `[T; 1]`.
-fn find_single_entry_with_same_repr<R: TypeResolver>(type_id: R::TypeId, types: &R) -> R::TypeId {
-    let v = visitor::new(type_id.clone(), |type_id, _| type_id)
-        .visit_tuple(|type_id, fields| {
-            let Some(new_type_id) = fields.next() else {
+// References and other pointers to things which can be encoded as fields can be too.
+impl<T: EncodeAsFields + ?Sized> EncodeAsFields for &T {
+    fn encode_as_fields_to<R: TypeResolver>(
+        &self,
+        fields: &mut dyn FieldIter<'_, R::TypeId>,
+        types: &R,
+        out: &mut Vec<u8>,
+    ) -> Result<(), Error> {
+        (**self).encode_as_fields_to(fields, types, out)
+    }
+}
+impl<T: EncodeAsFields + ?Sized> EncodeAsFields for &mut T {
+    fn encode_as_fields_to<R: TypeResolver>(
+        &self,
+        fields: &mut dyn FieldIter<'_, R::TypeId>,
+        types: &R,
+        out: &mut Vec<u8>,
+    ) -> Result<(), Error> {
+        (**self).encode_as_fields_to(fields, types, out)
+    }
+}
+impl<'a, T: EncodeAsFields + ToOwned + ?Sized> EncodeAsFields for alloc::borrow::Cow<'a, T> {
+    fn encode_as_fields_to<R: TypeResolver>(
+        &self,
+        fields: &mut dyn FieldIter<'_, R::TypeId>,
+        types: &R,
+        out: &mut Vec<u8>,
+    ) -> Result<(), Error> {
+        (**self).encode_as_fields_to(fields, types, out)
+    }
+}
+impl<P> EncodeAsFields for Pin<P>
+where
+    P: Deref,
+    P::Target: EncodeAsFields,
+{
+    fn encode_as_fields_to<R: TypeResolver>(
+        &self,
+        fields: &mut dyn FieldIter<'_, R::TypeId>,
+        types: &R,
+        out: &mut Vec<u8>,
+    ) -> Result<(), Error> {
+        (**self).encode_as_fields_to(fields, types, out)
+    }
+}
+
+// Attempt to recurse into some type, returning the innermost type found that has an identical
+// SCALE encoded representation to the given type. For instance, `(T,)` encodes identically to
+// `T`, as does `Mytype { inner: T }` or `[T; 1]`.
+fn find_single_entry_with_same_repr<R: TypeResolver>(type_id: R::TypeId, types: &R) -> R::TypeId {
+    let v = visitor::new(type_id.clone(), |type_id, _| type_id)
+        .visit_tuple(|type_id, fields| {
+            let Some(new_type_id) = fields.next() else {
                 return type_id;
             };
             if fields.next().is_some() {
@@ -559,77 +1065,187 @@ where
             expected_id: format!("{type_id:?}"),
         })
     };
+    // Sequences can't be spread across the fields of a struct or tuple, so give a
+    // clearer error than a generic shape mismatch if somebody tries this.
+    let too_many_entries_err = |type_id, num_entries| {
+        Error::new(ErrorKind::WrongShapeWithHint {
+            actual: Kind::Array,
+            expected_id: format!("{type_id:?}"),
+            hint: format!("it has {num_entries} fields, but sequences can only be encoded into single field wrappers around sequences or arrays"),
+        })
+    };
 
     let v = visitor::new((type_id.clone(), it, out), |(type_id, _, _), _| {
         Err(wrong_shape_err(type_id))
     })
     .visit_array(|(_, it, out), inner_ty_id: R::TypeId, array_len| {
-        if array_len == len {
-            for (idx, item) in it.enumerate() {
-                item.encode_as_type_to(inner_ty_id.clone(), types, out)
-                    .map_err(|e| e.at_idx(idx))?;
-            }
-            Ok(())
-        } else {
-            Err(Error::new(ErrorKind::WrongLength {
-                actual_len: len,
-                expected_len: array_len,
-            }))
-        }
+        encode_array_items_to(len, it, inner_ty_id, array_len, types, out)
     })
     .visit_sequence(|(_, it, out), _, inner_ty_id| {
-        // Sequences are prefixed with their compact encoded length:
-        Compact(len as u32).encode_to(out);
-        for (idx, item) in it.enumerate() {
-            item.encode_as_type_to(inner_ty_id.clone(), types, out)
-                .map_err(|e| e.at_idx(idx))?;
-        }
-        Ok(())
+        encode_sequence_items_to(len, it, inner_ty_id, types, out)
+    })
+    .visit_tuple(
+        |(type_id, it, out), inner_type_ids| match inner_type_ids.len() {
+            0 => Err(wrong_shape_err(type_id)),
+            1 => encode_iterable_sequence_to(len, it, inner_type_ids.next().unwrap(), types, out),
+            n => Err(too_many_entries_err(type_id, n)),
+        },
+    )
+    .visit_composite(|(type_id, it, out), _, fields| match fields.len() {
+        0 => Err(wrong_shape_err(type_id)),
+        1 => encode_iterable_sequence_to(len, it, fields.next().unwrap().id, types, out),
+        n => Err(too_many_entries_err(type_id, n)),
+    });
+
+    resolve_type_and_encode(types, type_id, v)
+}
+
+// Encode some iterator of items into an array of the given length, whose items have the
+// type given.
+fn encode_array_items_to<I, R>(
+    len: usize,
+    it: I,
+    inner_ty_id: R::TypeId,
+    array_len: usize,
+    types: &R,
+    out: &mut Vec<u8>,
+) -> Result<(), Error>
+where
+    I: Iterator,
+    I::Item: EncodeAsType,
+    R: TypeResolver,
+{
+    if array_len != len {
+        return Err(Error::new(ErrorKind::WrongLength {
+            actual_len: len,
+            expected_len: array_len,
+        }));
+    }
+
+    trace_decision!("encode {len} values as an array");
+    reserve_for_values(len, inner_ty_id.clone(), types, out);
+    for (idx, item) in it.enumerate() {
+        collect::nested(
+            || item.encode_as_type_to(inner_ty_id.clone(), types, out),
+            |e| e.at_idx(idx),
+        )?;
+    }
+    Ok(())
+}
+
+// Encode some iterator of items into a sequence whose items have the type given.
+fn encode_sequence_items_to<I, R>(
+    len: usize,
+    it: I,
+    inner_ty_id: R::TypeId,
+    types: &R,
+    out: &mut Vec<u8>,
+) -> Result<(), Error>
+where
+    I: Iterator,
+    I::Item: EncodeAsType,
+    R: TypeResolver,
+{
+    // Sequences are prefixed with their compact encoded length:
+    trace_decision!("encode {len} values as a sequence");
+    // (we write the prefix first so that the space reserved for values comes after it).
+    Compact(len as u32).encode_to(out);
+    reserve_for_values(len, inner_ty_id.clone(), types, out);
+    for (idx, item) in it.enumerate() {
+        collect::nested(
+            || item.encode_as_type_to(inner_ty_id.clone(), types, out),
+            |e| e.at_idx(idx),
+        )?;
+    }
+    Ok(())
+}
+
+// Encode some numbers to the type provided. If the target is an array or sequence of
+// the same primitive type then we can write them straight to the output without resolving
+// the type of each one, which is common for things like hashes and signatures (in the case
+// of bytes). Else, we fall back to encoding each number individually.
+fn encode_scalars_to<T: EncodeAsType, R: TypeResolver>(
+    scalars: &[T],
+    primitive: Primitive,
+    encode_scalars: fn(&[T], &mut Vec<u8>),
+    type_id: R::TypeId,
+    types: &R,
+    out: &mut Vec<u8>,
+) -> Result<(), Error> {
+    let len = scalars.len();
+    let is_same_primitive = |type_id: R::TypeId| {
+        let type_id = find_single_entry_with_same_repr(type_id, types);
+        let v = visitor::new((), |_, _| false).visit_primitive(|_, p| p == primitive);
+        types.resolve_type(type_id, v).unwrap_or(false)
+    };
+
+    let v = visitor::new((type_id.clone(), out), |(type_id, out), _| {
+        encode_iterable_sequence_to(len, scalars.iter(), type_id, types, out)
     })
-    .visit_tuple(|(type_id, it, out), inner_type_ids| {
-        if inner_type_ids.len() == 1 {
-            encode_iterable_sequence_to(len, it, inner_type_ids.next().unwrap(), types, out)
+    .visit_array(|(_, out), inner_ty_id: R::TypeId, array_len| {
+        if array_len == len && is_same_primitive(inner_ty_id.clone()) {
+            trace_decision!("copy {len} {primitive:?} values into an array");
+            encode_scalars(scalars, out);
+            Ok(())
         } else {
-            Err(wrong_shape_err(type_id))
+            encode_array_items_to(len, scalars.iter(), inner_ty_id, array_len, types, out)
         }
     })
-    .visit_composite(|(type_id, it, out), _, fields| {
-        if fields.len() == 1 {
-            encode_iterable_sequence_to(len, it, fields.next().unwrap().id, types, out)
+    .visit_sequence(|(_, out), _, inner_ty_id| {
+        if is_same_primitive(inner_ty_id.clone()) {
+            trace_decision!("copy {len} {primitive:?} values into a sequence");
+            Compact(len as u32).encode_to(out);
+            encode_scalars(scalars, out);
+            Ok(())
         } else {
-            Err(wrong_shape_err(type_id))
+            encode_sequence_items_to(len, scalars.iter(), inner_ty_id, types, out)
         }
+    })
+    .visit_bit_sequence(|(type_id, _), _, _| {
+        // Numbers (and bytes especially) are not treated as bits, so help anybody
+        // who might be expecting this to work.
+        Err(Error::new(ErrorKind::WrongShapeWithHint {
+            actual: Kind::Array,
+            expected_id: format!("{type_id:?}"),
+            hint: format!("{primitive:?} values are not treated as bits; bits should be encoded from a scale_bits::Bits instead"),
+        }))
     });
 
     resolve_type_and_encode(types, type_id, v)
 }
 
+// The most space that we'll reserve in the output up front. Sizes are worked out from
+// type information which hasn't been checked against the values being encoded yet, so
+// we don't trust them enough to allocate any more than this ahead of time.
+const MAX_RESERVE_BYTES: usize = 1024 * 1024;
+
+// Reserve space in the output for some number of values of the given type, to avoid
+// reallocating as each is encoded. Values whose encoded size isn't fixed are
+// assumed to take up at least one byte each.
+fn reserve_for_values<R: TypeResolver>(
+    len: usize,
+    type_id: R::TypeId,
+    types: &R,
+    out: &mut Vec<u8>,
+) {
+    let value_size = pre_encoded::fixed_encoded_size(type_id, types)
+        .ok()
+        .flatten()
+        .unwrap_or(1);
+    out.reserve(len.saturating_mul(value_size).min(MAX_RESERVE_BYTES));
+}
+
 #[cfg(all(feature = "derive", feature = "bits", feature = "primitive-types"))]
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::testing::{encode_type, make_type};
     use crate::{EncodeAsFields, Field};
     use alloc::vec;
     use codec::Decode;
     use core::fmt::Debug;
     use scale_info::{PortableRegistry, TypeInfo};
 
-    /// Given a type definition, return type ID and registry representing it.
-    fn make_type<T: TypeInfo + 'static>() -> (u32, PortableRegistry) {
-        let m = scale_info::MetaType::new::<T>();
-        let mut types = scale_info::Registry::new();
-        let id = types.register_type(&m);
-        let portable_registry: PortableRegistry = types.into();
-
-        (id.id, portable_registry)
-    }
-
-    fn encode_type<V: EncodeAsType, T: TypeInfo + 'static>(value: V) -> Result<Vec<u8>, Error> {
-        let (type_id, types) = make_type::<T>();
-        let bytes = value.encode_as_type(type_id, &types)?;
-        Ok(bytes)
-    }
-
     fn assert_value_roundtrips_to<
         V: EncodeAsType,
         T: PartialEq + Debug + Decode + TypeInfo + 'static,
@@ -654,7 +1270,7 @@ mod test {
         value: V,
     ) {
         let encode_bytes = value.encode();
-        let bytes = encode_type::<V, V>(value).expect("can encode");
+        let bytes = encode_type::<V, V>(&value).expect("can encode");
         assert_eq!(
             bytes, encode_bytes,
             "scale-encode encoded differently from parity-scale-codec"
@@ -806,6 +1422,21 @@ mod test {
         assert_value_roundtrips_to(v, vec![1u8, 2, 3]);
     }
 
+    #[test]
+    fn linked_lists_encode_to_arrays_of_the_same_length() {
+        let v = LinkedList::from([1u8, 2, 3]);
+        assert_value_roundtrips_to(&v, [1u64, 2, 3]);
+
+        let err = encode_type::<_, [u64; 2]>(&v).unwrap_err();
+        assert!(matches!(
+            err.kind(),
+            ErrorKind::WrongLength {
+                actual_len: 3,
+                expected_len: 2
+            }
+        ));
+    }
+
     #[test]
     fn btreemap_can_encode_to_struct() {
         #[derive(Debug, scale_info::TypeInfo, codec::Decode, PartialEq)]
@@ -1105,6 +1736,30 @@ mod test {
         )
     }
 
+    #[test]
+    fn encode_as_fields_works_through_pointers() {
+        use alloc::borrow::Cow;
+
+        #[derive(TypeInfo, Encode)]
+        struct Foo {
+            some_field: u64,
+            another: u8,
+        }
+        let foo = || Foo {
+            some_field: 3,
+            another: 2,
+        };
+
+        let mut map = BTreeMap::from([("another", 2), ("some_field", 3)]);
+        assert_encodes_fields_like_type(&map, foo());
+        assert_encodes_fields_like_type(Rc::new(&map), foo());
+        assert_encodes_fields_like_type(Cow::Borrowed(&map), foo());
+        assert_encodes_fields_like_type(Cow::<BTreeMap<_, _>>::Owned(map.clone()), foo());
+        assert_encodes_fields_like_type(Box::pin(map.clone()), foo());
+        assert_encodes_fields_like_type(Pin::new(&map), foo());
+        assert_encodes_fields_like_type(&mut map, foo());
+    }
+
     #[test]
     fn encode_as_fields_via_macro_works() {
         #[derive(TypeInfo, Encode)]
@@ -1297,4 +1952,2672 @@ mod test {
             }),
         );
     }
+
+    #[test]
+    fn pre_encoded_bytes_are_passed_through() {
+        let bytes = (123u32, true).encode();
+        assert_eq!(
+            encode_type::<_, (u32, bool)>(&PreEncoded(&bytes)).unwrap(),
+            bytes
+        );
+        assert_eq!(
+            encode_type::<_, (u32, bool)>(&CheckedPreEncoded(&bytes)).unwrap(),
+            bytes
+        );
+
+        // No validation is done, so bytes are passed through even if they
+        // aren't a valid encoding of the target type:
+        assert_eq!(
+            encode_type::<_, (u32, bool)>(&PreEncoded(&[1, 2, 3])).unwrap(),
+            vec![1, 2, 3]
+        );
+    }
+
+    #[test]
+    fn codec_values_are_encoded_via_encode() {
+        #[derive(Encode, TypeInfo)]
+        struct Foo {
+            a: u32,
+            b: bool,
+            c: String,
+        }
+        let foo = Foo {
+            a: 123,
+            b: true,
+            c: "hello".into(),
+        };
+        assert_eq!(encode_type::<_, Foo>(&Codec(&foo)).unwrap(), foo.encode());
+
+        // Fixed size targets are checked:
+        assert_eq!(
+            encode_type::<_, (u32, bool)>(&Codec((1u32, true))).unwrap(),
+            (1u32, true).encode()
+        );
+        let err = encode_type::<_, (u32, bool)>(&Codec((1u64, true))).unwrap_err();
+        assert!(matches!(
+            err.kind(),
+            ErrorKind::WrongLength {
+                actual_len: 9,
+                expected_len: 5
+            }
+        ));
+    }
+
+    #[test]
+    fn checked_pre_encoded_bytes_must_have_correct_length() {
+        let err = encode_type::<_, (u32, bool)>(&CheckedPreEncoded(&[1, 2, 3])).unwrap_err();
+        assert!(matches!(
+            err.kind(),
+            ErrorKind::WrongLength {
+                actual_len: 3,
+                expected_len: 5
+            }
+        ));
+
+        // Nested arrays and composites have a known size:
+        #[derive(TypeInfo)]
+        #[allow(dead_code)]
+        struct Foo {
+            a: [u16; 3],
+            b: (bool, char),
+        }
+        let bytes = [0u8; 11];
+        assert_eq!(
+            encode_type::<_, Foo>(&CheckedPreEncoded(&bytes)).unwrap(),
+            bytes
+        );
+
+        // The size of sequences, strings and compact values isn't known up front:
+        let bytes = vec![1u8].encode();
+        encode_type::<_, Vec<u8>>(&CheckedPreEncoded(&bytes)).unwrap_err();
+        encode_type::<_, String>(&CheckedPreEncoded(&bytes)).unwrap_err();
+        encode_type::<_, Compact<u8>>(&CheckedPreEncoded(&bytes)).unwrap_err();
+    }
+
+    #[test]
+    fn dyn_encode_as_fields_can_be_boxed() {
+        use crate::DynEncodeAsFields;
+
+        #[derive(TypeInfo, Encode)]
+        struct Foo {
+            some_field: u64,
+            another: u8,
+        }
+
+        #[derive(EncodeAsType)]
+        #[encode_as_type(crate_path = "crate")]
+        struct Bar {
+            another: u16,
+            some_field: u32,
+        }
+
+        let values: Vec<Box<dyn DynEncodeAsFields<PortableRegistry>>> = vec![
+            Box::new(Bar {
+                another: 2,
+                some_field: 3,
+            }),
+            Box::new(BTreeMap::from([("another", 2u8), ("some_field", 3u8)])),
+        ];
+
+        let (type_id, types) = make_type::<Foo>();
+        let scale_info::TypeDef::Composite(c) = &types.resolve(type_id).unwrap().type_def else {
+            panic!("Expected composite type def");
+        };
+
+        let expected = Foo {
+            some_field: 3,
+            another: 2,
+        }
+        .encode();
+        for value in &values {
+            let mut fields = c
+                .fields
+                .iter()
+                .map(|f| Field::new(f.ty.id, f.name.as_deref()));
+            let mut out = Vec::new();
+            value
+                .encode_as_fields_with_resolver_to(&mut fields, &types, &mut out)
+                .unwrap();
+            assert_eq!(out, expected);
+        }
+    }
+
+    #[test]
+    fn wrapping_and_saturating_encode_like_inner_numbers() {
+        // Widening works:
+        assert_value_roundtrips_to(Wrapping(300u16), 300u32);
+        assert_value_roundtrips_to(Wrapping(300u16), 300u64);
+        assert_value_roundtrips_to(Saturating(300u16), 300u32);
+        assert_value_roundtrips_to(Saturating(300u16), 300u64);
+
+        // Narrowing fails if the number doesn't fit:
+        let err = encode_type::<_, u8>(&Wrapping(300u16)).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::NumberOutOfRange { .. }));
+        let err = encode_type::<_, u8>(&Saturating(300u16)).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::NumberOutOfRange { .. }));
+
+        // Compact targets are handled:
+        assert_value_roundtrips_to(Wrapping(300u16), Compact(300u32));
+        assert_value_roundtrips_to(Saturating(300u16), Compact(300u32));
+        let err = encode_type::<_, Compact<u8>>(&Wrapping(300u16)).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::NumberOutOfRange { .. }));
+    }
+
+    #[test]
+    fn number_wrappers_encode_as_array_elements() {
+        let one = NonZeroU8::new(1).unwrap();
+        assert_value_roundtrips_to([one; 3], [1u8; 3]);
+        assert_value_roundtrips_to([one; 3], [1u64; 3]);
+        assert_value_roundtrips_to([Wrapping(1u16), Wrapping(2)], [1u8, 2]);
+        assert_value_roundtrips_to(vec![Saturating(1u16), Saturating(2)], [1u8, 2]);
+
+        // Errors point at the element which didn't fit:
+        let err =
+            encode_type::<_, [u8; 3]>(&[Wrapping(1u16), Wrapping(2), Wrapping(300)]).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::NumberOutOfRange { .. }));
+        assert_eq!(err.context().path().to_string(), "[2]");
+
+        let nums = [NonZeroU16::new(1).unwrap(), NonZeroU16::new(256).unwrap()];
+        let err = encode_type::<_, Vec<u8>>(&nums).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::NumberOutOfRange { .. }));
+        assert_eq!(err.context().path().to_string(), "[1]");
+    }
+
+    #[test]
+    fn dyn_encode_as_type_slices_encode_like_tuples() {
+        #[derive(Debug, TypeInfo, Encode, Decode, PartialEq)]
+        struct Foo {
+            a: u64,
+            b: bool,
+            c: String,
+        }
+
+        let s = "hello".to_string();
+        let values: [&dyn DynEncodeAsType<PortableRegistry>; 3] = [&1u8, &true, &s];
+        let expected = Foo {
+            a: 1,
+            b: true,
+            c: "hello".to_string(),
+        }
+        .encode();
+
+        // Encode into a struct:
+        let (type_id, types) = make_type::<Foo>();
+        let mut out = Vec::new();
+        values[..]
+            .encode_as_type_with_resolver_to(type_id, &types, &mut out)
+            .unwrap();
+        assert_eq!(out, expected);
+
+        // Encode into the struct fields:
+        let scale_info::TypeDef::Composite(c) = &types.resolve(type_id).unwrap().type_def else {
+            panic!("Expected composite type def");
+        };
+        let mut fields = c
+            .fields
+            .iter()
+            .map(|f| Field::new(f.ty.id, f.name.as_deref()));
+        let mut out = Vec::new();
+        values[..]
+            .encode_as_fields_with_resolver_to(&mut fields, &types, &mut out)
+            .unwrap();
+        assert_eq!(out, expected);
+
+        // Each value is encoded against the corresponding element type in sequences:
+        let values: [&dyn DynEncodeAsType<PortableRegistry>; 3] = [&1u8, &2u64, &(3u16,)];
+        let (type_id, types) = make_type::<Vec<u32>>();
+        let mut out = Vec::new();
+        values[..]
+            .encode_as_type_with_resolver_to(type_id, &types, &mut out)
+            .unwrap();
+        assert_eq!(out, vec![1u32, 2, 3].encode());
+
+        // Errors point at the failing index:
+        let values: [&dyn DynEncodeAsType<PortableRegistry>; 2] = [&1u8, &true];
+        let err = values[..]
+            .encode_as_type_with_resolver_to(type_id, &types, &mut Vec::new())
+            .unwrap_err();
+        assert_eq!(err.context().path().to_string(), "[1]");
+    }
+
+    #[test]
+    fn range_encodes_positionally_into_named_struct() {
+        #[derive(Debug, TypeInfo, Decode, PartialEq)]
+        struct RangeTarget {
+            start: u32,
+            end: u32,
+        }
+
+        // Ranges are encoded like unnamed tuples, so line up by position:
+        assert_value_roundtrips_to(0u8..10, RangeTarget { start: 0, end: 10 });
+        assert_value_roundtrips_to(0u8..=10, RangeTarget { start: 0, end: 10 });
+    }
+
+    #[test]
+    fn range_encodes_into_sequences_and_arrays_of_two() {
+        assert_value_roundtrips_to(0u8..10, [0u32, 10]);
+        assert_value_roundtrips_to(0u8..10, vec![0u32, 10]);
+        assert_value_roundtrips_to(0u8..=10, [0u32, 10]);
+        assert_value_roundtrips_to(0u8..=10, vec![0u32, 10]);
+
+        // Arrays of other lengths aren't valid targets:
+        let err = encode_type::<_, [u32; 3]>(&(0u8..10)).unwrap_err();
+        assert!(matches!(
+            err.kind(),
+            ErrorKind::WrongLength {
+                actual_len: 2,
+                expected_len: 3
+            }
+        ));
+    }
+
+    #[test]
+    fn variant_field_errors_have_variant_and_field_context() {
+        use crate::error::Location;
+
+        #[derive(EncodeAsType)]
+        #[encode_as_type(crate_path = "crate")]
+        enum Foo {
+            #[allow(dead_code)]
+            Other,
+            MyVariant {
+                my_field: u64,
+            },
+        }
+
+        #[derive(TypeInfo)]
+        #[allow(dead_code)]
+        enum FooTarget {
+            Other,
+            MyVariant { my_field: u8 },
+        }
+
+        #[derive(EncodeAsType)]
+        #[encode_as_type(crate_path = "crate")]
+        struct Outer {
+            inner: Foo,
+        }
+
+        #[derive(TypeInfo)]
+        #[allow(dead_code)]
+        struct OuterTarget {
+            inner: FooTarget,
+        }
+
+        let err = encode_type::<_, FooTarget>(&Foo::MyVariant { my_field: 1000 }).unwrap_err();
+        let locations: Vec<_> = err.context().path().locations().cloned().collect();
+        assert_eq!(
+            locations,
+            vec![Location::variant("MyVariant"), Location::field("my_field")]
+        );
+        assert!(matches!(err.kind(), ErrorKind::NumberOutOfRange { .. }));
+
+        // Locations are reported outermost first:
+        let err = encode_type::<_, OuterTarget>(&Outer {
+            inner: Foo::MyVariant { my_field: 1000 },
+        })
+        .unwrap_err();
+        assert_eq!(
+            err.context().path().to_string(),
+            "inner.(MyVariant).my_field"
+        );
+    }
+
+    #[test]
+    fn large_sequences_encode_ok() {
+        let bytes: Vec<u8> = (0..10_000).map(|n| n as u8).collect();
+        assert_encodes_like_codec(bytes.clone());
+        assert_value_roundtrips_to(bytes.clone(), bytes.clone());
+        assert_value_roundtrips_to(&bytes[..], bytes.clone());
+
+        // Widening each value, and encoding into arrays:
+        let nums: Vec<u16> = (0..1_000).collect();
+        let wide: Vec<u64> = (0..1_000).collect();
+        assert_value_roundtrips_to(nums.clone(), wide);
+        let arr: [u32; 1_000] = core::array::from_fn(|n| n as u32);
+        assert_value_roundtrips_to(nums, arr);
+
+        // Values whose size isn't fixed:
+        let strings: Vec<String> = (0..1_000).map(|n| n.to_string()).collect();
+        assert_encodes_like_codec(strings);
+    }
+
+    #[test]
+    fn btreemap_cannot_encode_to_variant() {
+        #[derive(TypeInfo)]
+        #[allow(dead_code)]
+        enum Foo {
+            A { a: u8 },
+            B { b: u8 },
+        }
+
+        let map = BTreeMap::from([("b", 1u8)]);
+        let err = encode_type::<_, Foo>(&map).unwrap_err();
+        assert!(matches!(
+            err.kind(),
+            ErrorKind::WrongShapeWithHint {
+                actual: Kind::Struct,
+                ..
+            }
+        ));
+        let msg = err.to_string();
+        assert!(msg.contains("a map cannot select an enum variant directly"));
+        assert!(msg.contains("scale_encode::Variant"));
+
+        // Following the advice, the map entries can fill the fields of a named variant:
+        let (type_id, types) = make_type::<Foo>();
+        let bytes = Variant {
+            name: "B",
+            fields: Composite::new(
+                map.iter()
+                    .map(|(name, val)| (Some(*name), CompositeField::new(val))),
+            ),
+        }
+        .encode_variant_as_type(type_id, &types)
+        .unwrap();
+        assert_eq!(bytes, vec![1, 1]);
+    }
+
+    #[test]
+    fn number_as_bool_encodes_to_bool() {
+        assert_value_roundtrips_to(NumberAsBool(0u8), false);
+        assert_value_roundtrips_to(NumberAsBool(1u8), true);
+        assert_value_roundtrips_to(NumberAsBool(1i64), true);
+        assert_value_roundtrips_to(NumberAsBool(0u128), (false,));
+
+        // Other numbers are out of range:
+        let err = encode_type::<_, bool>(&NumberAsBool(2u8)).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::NumberOutOfRange { .. }));
+        let err = encode_type::<_, bool>(&NumberAsBool(-1i32)).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::NumberOutOfRange { .. }));
+
+        // And only bool targets are allowed:
+        let err = encode_type::<_, u8>(&NumberAsBool(1u8)).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::WrongShape { .. }));
+    }
+
+    #[test]
+    fn skipped_variants_fail_to_encode() {
+        struct NotEncodeAsType;
+
+        #[derive(EncodeAsType)]
+        #[encode_as_type(crate_path = "crate")]
+        enum Foo {
+            A(u8),
+            #[encode_as_type(skip)]
+            Internal(NotEncodeAsType),
+            #[codec(skip)]
+            #[allow(dead_code)]
+            OtherInternal {
+                value: NotEncodeAsType,
+            },
+        }
+
+        #[derive(Debug, TypeInfo, Decode, PartialEq)]
+        enum FooTarget {
+            A(u8),
+        }
+
+        assert_value_roundtrips_to(Foo::A(1), FooTarget::A(1));
+
+        let err = encode_type::<_, FooTarget>(&Foo::Internal(NotEncodeAsType)).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::Custom(_)));
+        assert!(err
+            .to_string()
+            .contains("Variant Internal is not encodable"));
+    }
+
+    #[test]
+    fn phantom_pinned_encodes_like_unit() {
+        assert_value_roundtrips_to(PhantomPinned, ());
+        assert_value_roundtrips_to((1u8, PhantomPinned), (1u8, ()));
+    }
+
+    #[test]
+    fn ipv6_encodes_to_octets_or_segments() {
+        let addr = Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 1);
+
+        assert_value_roundtrips_to(addr, addr.octets());
+        assert_value_roundtrips_to(addr, addr.segments());
+        assert_value_roundtrips_to(addr, addr.octets().to_vec());
+
+        // Segments are encoded as little endian u16s, unlike the big endian octets:
+        let octets = encode_type::<_, [u8; 16]>(&addr).unwrap();
+        let segments = encode_type::<_, [u16; 8]>(&addr).unwrap();
+        assert_eq!(octets[..2], [0x20, 0x01]);
+        assert_eq!(segments[..2], [0x01, 0x20]);
+
+        // Other lengths are not possible:
+        encode_type::<_, [u8; 4]>(&addr).unwrap_err();
+    }
+
+    #[test]
+    fn tuple_errors_point_at_failing_index() {
+        use crate::error::Location;
+
+        // Widening each element is fine:
+        assert_value_roundtrips_to((1u8, 2u8), (1u64, 2u8));
+
+        // But the second element here is out of range:
+        let err = encode_type::<_, (u64, u8)>(&(2u8, 300u16)).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::NumberOutOfRange { .. }));
+        let locations: Vec<_> = err.context().path().locations().cloned().collect();
+        assert_eq!(locations, vec![Location::idx(1)]);
+        assert_eq!(err.context().path().to_string(), "[1]");
+
+        // Nested tuples give the full path:
+        let err = encode_type::<_, (u8, (u8, u8))>(&(1u8, (2u8, 300u16))).unwrap_err();
+        assert_eq!(err.context().path().to_string(), "[1].[1]");
+    }
+
+    #[test]
+    fn deeply_nested_values_error_cleanly_with_depth_limit() {
+        use crate::resolver::DepthLimited;
+
+        #[derive(EncodeAsType)]
+        #[encode_as_type(crate_path = "crate")]
+        enum List {
+            Cons(u8, Box<List>),
+            Nil,
+        }
+
+        #[derive(Debug, TypeInfo, Encode, Decode, PartialEq)]
+        enum ListTarget {
+            Cons(u64, Box<ListTarget>),
+            Nil,
+        }
+
+        fn make_list(len: usize) -> List {
+            (0..len).fold(List::Nil, |tail, n| List::Cons(n as u8, Box::new(tail)))
+        }
+
+        // Avoid recursing when dropping long lists.
+        fn drop_list(mut list: List) {
+            while let List::Cons(_, tail) = list {
+                list = *tail;
+            }
+        }
+
+        let (type_id, types) = make_type::<ListTarget>();
+
+        // Short lists encode fine within the limit:
+        let list = make_list(3);
+        let limited = DepthLimited::new(&types, 64);
+        let bytes = list.encode_as_type(type_id, &limited).unwrap();
+        let target = ListTarget::Cons(
+            2,
+            Box::new(ListTarget::Cons(
+                1,
+                Box::new(ListTarget::Cons(0, Box::new(ListTarget::Nil))),
+            )),
+        );
+        assert_eq!(bytes, target.encode());
+
+        // Long lists hit the limit and return an error rather than overflowing the stack:
+        let list = make_list(10_000);
+        let limited = DepthLimited::new(&types, 64);
+        let err = list.encode_as_type(type_id, &limited).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::TypeResolvingError(_)));
+        assert!(err.to_string().contains("depth of 64 exceeded"));
+        drop_list(list);
+    }
+
+    #[test]
+    fn byte_arrays_encode_like_scale_codec() {
+        let hash: [u8; 32] = core::array::from_fn(|n| n as u8);
+        let sig: [u8; 64] = core::array::from_fn(|n| (n * 3) as u8);
+
+        assert_encodes_like_codec(hash);
+        assert_encodes_like_codec(sig);
+        assert_encodes_like_codec(hash.to_vec());
+        assert_value_roundtrips_to(hash, hash.to_vec());
+        assert_value_roundtrips_to(hash.to_vec(), hash);
+        assert_value_roundtrips_to(&hash[..], ((hash,),));
+        assert_value_roundtrips_to(sig, ::primitive_types::H512::from(sig));
+
+        // Non byte targets still work:
+        let wide: [u64; 32] = core::array::from_fn(|n| n as u64);
+        assert_value_roundtrips_to(hash, wide);
+        assert_value_roundtrips_to(hash, wide.to_vec());
+
+        // Lengths must line up for arrays:
+        encode_type::<_, [u8; 31]>(&hash).unwrap_err();
+    }
+
+    #[test]
+    fn option_values_peel_single_field_wrappers() {
+        #[derive(EncodeAsType)]
+        #[encode_as_type(crate_path = "crate")]
+        struct Wrapper {
+            val: u8,
+        }
+
+        assert_value_roundtrips_to(Some((5u8,)), Some(5u64));
+        assert_value_roundtrips_to(Some(((5u8,),)), Some(5u64));
+        assert_value_roundtrips_to(Some(Wrapper { val: 5 }), Some(5u64));
+        assert_value_roundtrips_to(Some(5u8), Some((5u64,)));
+        assert_value_roundtrips_to(None::<Wrapper>, None::<u64>);
+    }
+
+    #[test]
+    fn btreeset_encodes_in_ascending_order() {
+        let set = BTreeSet::from([5u8, 1, 3, 2, 4]);
+        assert_value_roundtrips_to(set.clone(), vec![1u8, 2, 3, 4, 5]);
+        assert_value_roundtrips_to(set, [1u64, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn btreeset_cannot_encode_to_named_struct() {
+        #[derive(TypeInfo)]
+        #[allow(dead_code)]
+        struct Foo {
+            a: u8,
+            b: u8,
+        }
+
+        #[derive(Debug, TypeInfo, Decode, PartialEq)]
+        struct Wrapper {
+            inner: Vec<u8>,
+        }
+
+        let err = encode_type::<_, Foo>(&BTreeSet::from([1u8, 2])).unwrap_err();
+        assert!(matches!(
+            err.kind(),
+            ErrorKind::WrongShapeWithHint {
+                actual: Kind::Array,
+                ..
+            }
+        ));
+        assert!(err
+            .to_string()
+            .contains("a set cannot be encoded as a named struct"));
+
+        // Single field wrappers are still fine:
+        assert_value_roundtrips_to(BTreeSet::from([1u8, 2]), Wrapper { inner: vec![1, 2] });
+    }
+
+    #[test]
+    fn range_inclusive_encodes_endpoints_only() {
+        #[derive(TypeInfo)]
+        #[allow(dead_code)]
+        struct RangeTarget {
+            start: u32,
+            end: u32,
+        }
+
+        let inclusive = encode_type::<_, RangeTarget>(&(1u8..=10)).unwrap();
+        let exclusive = encode_type::<_, RangeTarget>(&(1u8..10)).unwrap();
+        assert_eq!(inclusive, (1u32, 10u32).encode());
+        // Inclusivity is not preserved; the same endpoints encode identically:
+        assert_eq!(inclusive, exclusive);
+    }
+
+    #[test]
+    fn tagged_values_override_target_type_id() {
+        #[derive(Debug, TypeInfo, Decode, PartialEq)]
+        struct Foo {
+            a: u8,
+            b: u8,
+        }
+
+        #[derive(EncodeAsType)]
+        #[encode_as_type(crate_path = "crate")]
+        struct Source<A> {
+            a: A,
+            b: u8,
+        }
+
+        // Register a u64 alongside Foo so that we have an ID to tag with.
+        let (type_id, types) = make_type::<(Foo, u64)>();
+        let scale_info::TypeDef::Tuple(t) = &types.resolve(type_id).unwrap().type_def else {
+            panic!("Expected tuple type def");
+        };
+        let (foo_id, u64_id) = (t.fields[0].id, t.fields[1].id);
+
+        // Field "a" is encoded as a u64 rather than a u8:
+        let source = Source {
+            a: Tagged::new(1000u64, u64_id),
+            b: 2,
+        };
+        let bytes = source.encode_as_type(foo_id, &types).unwrap();
+        assert_eq!(bytes, (1000u64, 2u8).encode());
+
+        // Type IDs of the wrong type can't be used:
+        let source = Source {
+            a: Tagged::new(1u8, u64_id as u64),
+            b: 2,
+        };
+        source.encode_as_type(foo_id, &types).unwrap_err();
+    }
+
+    #[test]
+    fn cow_slices_encode_to_arrays_with_length_check() {
+        use alloc::borrow::Cow;
+
+        let borrowed: Cow<'_, [u8]> = Cow::Borrowed(&[1u8, 2, 3]);
+        let owned: Cow<'_, [u8]> = Cow::Owned(vec![1u8, 2, 3]);
+
+        for cow in [borrowed, owned] {
+            assert_eq!(
+                encode_type::<_, [u64; 3]>(&cow).unwrap(),
+                [1u64, 2, 3].encode()
+            );
+            assert_eq!(
+                encode_type::<_, Vec<u64>>(&cow).unwrap(),
+                vec![1u64, 2, 3].encode()
+            );
+
+            let err = encode_type::<_, [u64; 2]>(&cow).unwrap_err();
+            assert!(matches!(
+                err.kind(),
+                ErrorKind::WrongLength {
+                    actual_len: 3,
+                    expected_len: 2
+                }
+            ));
+        }
+    }
+
+    #[test]
+    fn numbers_can_encode_to_chars() {
+        // Chars are encoded like u32s:
+        assert_eq!(encode_type::<_, char>(&65u32).unwrap(), 65u32.encode());
+        assert_eq!(encode_type::<_, char>(&65u8).unwrap(), 65u32.encode());
+        assert_eq!(
+            encode_type::<_, char>(&0x1F600u64).unwrap(),
+            ('😀' as u32).encode()
+        );
+
+        // Surrogates aren't valid chars:
+        let err = encode_type::<_, char>(&0xD800u32).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::NumberOutOfRange { .. }));
+        // Nor are negative numbers or values beyond the char range:
+        let err = encode_type::<_, char>(&-1i32).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::NumberOutOfRange { .. }));
+        let err = encode_type::<_, char>(&0x110000u64).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::NumberOutOfRange { .. }));
+    }
+
+    #[test]
+    fn non_zero_numbers_are_range_checked() {
+        use core::num::{NonZeroU32, NonZeroU8};
+
+        assert_value_roundtrips_to(NonZeroU8::new(1).unwrap(), 1u8);
+        assert_eq!(
+            encode_type::<_, u8>(&NonZeroU8::new(1).unwrap()).unwrap(),
+            vec![1]
+        );
+
+        let err = encode_type::<_, u8>(&NonZeroU32::new(256).unwrap()).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::NumberOutOfRange { .. }));
+    }
+
+    #[test]
+    fn all_non_zero_numbers_encode_like_their_primitives() {
+        // Encode the given value into the matching primitive, any wider ones given,
+        // and check that it fails to encode into the narrower primitive.
+        macro_rules! check_non_zero {
+            ($nz:ident($val:expr): $ty:ty, [$($wider:ty),*], $narrower:ty) => {{
+                let n = core::num::$nz::new($val).unwrap();
+                assert_value_roundtrips_to(n, $val as $ty);
+                $( assert_value_roundtrips_to(n, $val as $wider); )*
+                let err = encode_type::<_, $narrower>(&n).unwrap_err();
+                assert!(
+                    matches!(err.kind(), ErrorKind::NumberOutOfRange { .. }),
+                    "{} into {}: {err:?}",
+                    stringify!($nz),
+                    stringify!($narrower)
+                );
+            }};
+        }
+
+        check_non_zero!(NonZeroU8(u8::MAX): u8, [u16, i16], i8);
+        check_non_zero!(NonZeroU16(u16::MAX): u16, [u32, i32], u8);
+        check_non_zero!(NonZeroU32(u32::MAX): u32, [u64, i64], u16);
+        check_non_zero!(NonZeroU64(u64::MAX): u64, [u128, i128], u32);
+        check_non_zero!(NonZeroU128(u128::MAX): u128, [], u64);
+        check_non_zero!(NonZeroI8(i8::MIN): i8, [i16], u8);
+        check_non_zero!(NonZeroI16(i16::MIN): i16, [i32], i8);
+        check_non_zero!(NonZeroI32(i32::MIN): i32, [i64], i16);
+        check_non_zero!(NonZeroI64(i64::MIN): i64, [i128], i32);
+        check_non_zero!(NonZeroI128(i128::MIN): i128, [], i64);
+    }
+
+    #[test]
+    fn sequences_into_multi_field_composites_give_clear_error() {
+        #[derive(TypeInfo)]
+        #[allow(dead_code)]
+        struct Foo {
+            len: u32,
+            values: Vec<u8>,
+        }
+
+        let err = encode_type::<_, Foo>(&vec![1u8, 2, 3]).unwrap_err();
+        assert!(matches!(
+            err.kind(),
+            ErrorKind::WrongShapeWithHint {
+                actual: Kind::Array,
+                ..
+            }
+        ));
+        let msg = err.to_string();
+        assert!(msg.contains("it has 2 fields"), "{msg}");
+        assert!(msg.contains("single field wrappers"), "{msg}");
+
+        let err = encode_type::<_, (u8, u8)>(&vec![1u8, 2]).unwrap_err();
+        assert!(err.to_string().contains("it has 2 fields"));
+
+        // Single field wrappers are still fine:
+        #[derive(TypeInfo)]
+        #[allow(dead_code)]
+        struct Wrapper(Vec<u8>);
+        assert_eq!(
+            encode_type::<_, Wrapper>(&vec![1u8, 2, 3]).unwrap(),
+            vec![1u8, 2, 3].encode()
+        );
+    }
+
+    #[test]
+    fn empty_tuple_encodes_to_empty_sequences_and_arrays() {
+        assert_eq!(
+            encode_type::<_, Vec<u8>>(&()).unwrap(),
+            Vec::<u8>::new().encode()
+        );
+        assert_eq!(encode_type::<_, [u8; 0]>(&()).unwrap(), Vec::<u8>::new());
+        assert_eq!(encode_type::<_, ()>(&()).unwrap(), Vec::<u8>::new());
+
+        let err = encode_type::<_, [u8; 2]>(&()).unwrap_err();
+        assert!(matches!(
+            err.kind(),
+            ErrorKind::WrongLength {
+                actual_len: 0,
+                expected_len: 2
+            }
+        ));
+    }
+
+    #[test]
+    fn strings_into_variants_give_clear_error() {
+        #[derive(TypeInfo)]
+        #[allow(dead_code)]
+        enum Foo {
+            A,
+            B,
+        }
+
+        let err = encode_type::<_, Foo>(&"A").unwrap_err();
+        assert!(matches!(
+            err.kind(),
+            ErrorKind::WrongShapeWithHint {
+                actual: Kind::Str,
+                ..
+            }
+        ));
+        let msg = err.to_string();
+        assert!(
+            msg.contains("strings cannot select variants by name"),
+            "{msg}"
+        );
+        assert!(msg.contains("scale_encode::Variant"), "{msg}");
+    }
+
+    #[test]
+    fn flattened_fields_are_spliced_into_parent() {
+        #[derive(EncodeAsType)]
+        #[encode_as_type(crate_path = "crate")]
+        struct Inner {
+            a: u8,
+            b: bool,
+        }
+
+        #[derive(EncodeAsType)]
+        #[encode_as_type(crate_path = "crate")]
+        struct Outer {
+            #[encode_as_type(flatten)]
+            inner: Inner,
+            c: String,
+        }
+
+        #[derive(Debug, PartialEq, Decode, TypeInfo)]
+        struct Target {
+            c: String,
+            b: bool,
+            a: u64,
+        }
+
+        assert_value_roundtrips_to(
+            Outer {
+                inner: Inner { a: 1, b: true },
+                c: "hello".to_string(),
+            },
+            Target {
+                a: 1,
+                b: true,
+                c: "hello".to_string(),
+            },
+        );
+
+        // Unnamed fields can be flattened too, and are lined up by position:
+        #[derive(EncodeAsType)]
+        #[encode_as_type(crate_path = "crate")]
+        struct UnnamedOuter(u8, #[encode_as_type(flatten)] (u16, u32));
+        assert_value_roundtrips_to(UnnamedOuter(1, (2, 3)), (1u64, 2u64, 3u64));
+    }
+
+    #[test]
+    fn shared_byte_slices_encode_like_slices() {
+        use alloc::rc::Rc;
+        use alloc::sync::Arc;
+
+        let bytes: Vec<u8> = (0..100_000).map(|n| n as u8).collect();
+        let arc: Arc<[u8]> = bytes.clone().into();
+        let rc: Rc<[u8]> = bytes.clone().into();
+        let boxed: Box<[u8]> = bytes.clone().into();
+
+        let expected = bytes.encode();
+        assert_eq!(encode_type::<_, Vec<u8>>(&arc).unwrap(), expected);
+        assert_eq!(encode_type::<_, Vec<u8>>(&rc).unwrap(), expected);
+        assert_eq!(encode_type::<_, Vec<u8>>(&boxed).unwrap(), expected);
+
+        // Array targets and other element types work too:
+        let arc: Arc<[u8]> = Arc::from([1u8, 2, 3]);
+        assert_eq!(
+            encode_type::<_, [u64; 3]>(&arc).unwrap(),
+            [1u64, 2, 3].encode()
+        );
+        let rc: Rc<[u16]> = Rc::from([1u16, 2, 3]);
+        assert_eq!(
+            encode_type::<_, Vec<u8>>(&rc).unwrap(),
+            vec![1u8, 2, 3].encode()
+        );
+    }
+
+    #[test]
+    fn duration_encodes_to_compact_targets() {
+        let d = Duration::new(1_000_000, 500);
+        assert_eq!(
+            encode_type::<_, (Compact<u64>, Compact<u32>)>(&d).unwrap(),
+            (Compact(1_000_000u64), Compact(500u32)).encode()
+        );
+
+        #[derive(TypeInfo)]
+        #[allow(dead_code)]
+        struct CompactDuration {
+            #[codec(compact)]
+            secs: u64,
+            #[codec(compact)]
+            nanos: u32,
+        }
+        assert_eq!(
+            encode_type::<_, CompactDuration>(&d).unwrap(),
+            (Compact(1_000_000u64), Compact(500u32)).encode()
+        );
+
+        // Compact targets are range checked like any other number:
+        let err = encode_type::<_, (Compact<u16>, Compact<u32>)>(&d).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::NumberOutOfRange { .. }));
+    }
+
+    #[test]
+    fn repr_transparent_structs_delegate_to_their_field() {
+        #[derive(EncodeAsType)]
+        #[encode_as_type(crate_path = "crate")]
+        struct Inner {
+            a: u8,
+            b: bool,
+        }
+
+        #[derive(EncodeAsType)]
+        #[encode_as_type(crate_path = "crate")]
+        #[repr(transparent)]
+        struct Transparent {
+            inner: Inner,
+        }
+
+        #[derive(EncodeAsType)]
+        #[encode_as_type(crate_path = "crate")]
+        struct NotTransparent {
+            inner: Inner,
+        }
+
+        #[derive(Debug, PartialEq, Decode, TypeInfo)]
+        struct Target {
+            b: bool,
+            a: u64,
+        }
+
+        // The field name "inner" is not used to line up with the target fields:
+        assert_value_roundtrips_to(
+            Transparent {
+                inner: Inner { a: 1, b: true },
+            },
+            Target { a: 1, b: true },
+        );
+        // Whereas without repr(transparent), the struct is treated as a composite:
+        let err = encode_type::<_, Target>(&NotTransparent {
+            inner: Inner { a: 1, b: true },
+        })
+        .unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::CannotFindField { .. }));
+
+        // Zero sized fields which are skipped are ignored:
+        #[derive(EncodeAsType)]
+        #[encode_as_type(crate_path = "crate")]
+        #[repr(transparent)]
+        struct W(u64, #[codec(skip)] core::marker::PhantomData<u8>);
+        assert_value_roundtrips_to(W(123, core::marker::PhantomData), 123u64);
+    }
+
+    #[test]
+    fn repr_transparent_structs_encode_into_same_shaped_targets() {
+        #[derive(EncodeAsType)]
+        #[encode_as_type(crate_path = "crate")]
+        struct Inner {
+            a: u8,
+        }
+
+        #[derive(EncodeAsType)]
+        #[encode_as_type(crate_path = "crate")]
+        #[repr(transparent)]
+        struct Wrapper {
+            inner: Inner,
+        }
+
+        #[derive(Debug, PartialEq, Decode, TypeInfo)]
+        struct TargetInner {
+            a: u64,
+        }
+
+        #[derive(Debug, PartialEq, Decode, TypeInfo)]
+        struct TargetWrapper {
+            inner: TargetInner,
+        }
+
+        // The fields line up by name, so we don't need to delegate to the inner field:
+        assert_value_roundtrips_to(
+            Wrapper {
+                inner: Inner { a: 1 },
+            },
+            TargetWrapper {
+                inner: TargetInner { a: 1 },
+            },
+        );
+    }
+
+    #[test]
+    fn btreemap_errors_are_attributed_to_fields() {
+        #[derive(TypeInfo)]
+        #[allow(dead_code)]
+        struct Foo {
+            a: u8,
+            b: u8,
+        }
+
+        let map = BTreeMap::from([("a", 1u64), ("b", 1000u64)]);
+        let err = encode_type::<_, Foo>(&map).unwrap_err();
+
+        assert!(matches!(err.kind(), ErrorKind::NumberOutOfRange { .. }));
+        assert_eq!(err.context().path().to_string(), "b");
+        assert_eq!(
+            err.context().locations().next().and_then(|l| l.as_field()),
+            Some("b")
+        );
+    }
+
+    #[test]
+    fn nested_btreemaps_encode_to_nested_structs() {
+        #[derive(Debug, PartialEq, Encode, Decode, TypeInfo)]
+        struct Inner1 {
+            x: u8,
+            y: u16,
+        }
+        #[derive(Debug, PartialEq, Encode, Decode, TypeInfo)]
+        struct Inner2 {
+            z: u32,
+        }
+        #[derive(Debug, PartialEq, Encode, Decode, TypeInfo)]
+        struct Outer {
+            a: Inner1,
+            b: Inner2,
+        }
+
+        let map = BTreeMap::from([
+            ("b", BTreeMap::from([("z", 3u8)])),
+            ("a", BTreeMap::from([("y", 2u8), ("x", 1u8)])),
+        ]);
+        assert_value_roundtrips_to(
+            map,
+            Outer {
+                a: Inner1 { x: 1, y: 2 },
+                b: Inner2 { z: 3 },
+            },
+        );
+
+        // Errors in nested maps point to the nested field:
+        let map = BTreeMap::from([
+            ("a", BTreeMap::from([("x", 1000u64), ("y", 2)])),
+            ("b", BTreeMap::from([("z", 3)])),
+        ]);
+        let err = encode_type::<_, Outer>(&map).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::NumberOutOfRange { .. }));
+        assert_eq!(err.context().path().to_string(), "a.x");
+
+        // Missing nested fields are reported too:
+        let map = BTreeMap::from([
+            ("a", BTreeMap::from([("x", 1u8), ("y", 2)])),
+            ("b", BTreeMap::from([("nope", 3)])),
+        ]);
+        let err = encode_type::<_, Outer>(&map).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::CannotFindField { name } if name == "z"));
+        assert_eq!(err.context().path().to_string(), "b");
+    }
+
+    #[test]
+    fn options_in_sequences_encode_like_codec() {
+        let value = vec![Some(1u8), None, Some(3u8)];
+        assert_eq!(
+            encode_type::<_, Vec<Option<u64>>>(&value).unwrap(),
+            vec![Some(1u64), None, Some(3u64)].encode()
+        );
+        assert_value_roundtrips_to(value, vec![Some(1u64), None, Some(3u64)]);
+    }
+
+    #[test]
+    fn string_sequences_encode_like_codec() {
+        let strings = vec!["a".to_string(), "b".to_string()];
+        let strs = vec!["a", "b"];
+
+        assert_value_roundtrips_to(strings.clone(), strings.clone());
+        assert_value_roundtrips_to(strs.clone(), strings.clone());
+        assert_value_roundtrips_to(&strs[..], strings.clone());
+        assert_eq!(
+            encode_type::<_, Vec<String>>(&strs[..]).unwrap(),
+            strs.encode()
+        );
+    }
+
+    #[test]
+    fn empty_btreemap_encodes_to_empty_targets() {
+        #[derive(TypeInfo)]
+        struct Empty {}
+
+        let map = BTreeMap::<String, u8>::new();
+        assert_eq!(
+            encode_type::<_, Vec<u8>>(&map).unwrap(),
+            Vec::<u8>::new().encode()
+        );
+        assert_eq!(encode_type::<_, [u8; 0]>(&map).unwrap(), Vec::<u8>::new());
+        assert_eq!(encode_type::<_, Empty>(&map).unwrap(), Vec::<u8>::new());
+        assert_eq!(encode_type::<_, ()>(&map).unwrap(), Vec::<u8>::new());
+    }
+
+    #[test]
+    fn sequence_errors_are_attributed_to_indexes() {
+        let err = encode_type::<_, Vec<u8>>(&vec![1u64, 9999, 3]).unwrap_err();
+
+        assert!(matches!(err.kind(), ErrorKind::NumberOutOfRange { .. }));
+        assert_eq!(err.context().path().to_string(), "[1]");
+        assert_eq!(err.context().len(), 1);
+        assert_eq!(
+            err.context().locations().next().and_then(|l| l.as_idx()),
+            Some(1)
+        );
+    }
+
+    #[test]
+    fn refcell_guards_encode_like_their_values() {
+        use core::cell::RefCell;
+
+        let cell = RefCell::new((1u8, "hello".to_string()));
+        assert_value_roundtrips_to(cell.borrow(), (1u64, "hello".to_string()));
+        assert_value_roundtrips_to(cell.borrow_mut(), (1u64, "hello".to_string()));
+    }
+
+    #[test]
+    fn result_errors_are_attributed_to_variants() {
+        #[derive(TypeInfo)]
+        #[allow(dead_code)]
+        struct ErrTarget {
+            code: u8,
+        }
+
+        let err = encode_type::<_, Result<u64, ErrTarget>>(&Err::<u64, _>(1000u64)).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::NumberOutOfRange { .. }));
+        // The variant comes first, followed by the (unnamed) field within it:
+        assert_eq!(err.context().path().to_string(), "(Err).[0]");
+        assert_eq!(
+            err.context()
+                .locations()
+                .next()
+                .and_then(|l| l.as_variant()),
+            Some("Err")
+        );
+
+        let err = encode_type::<_, Result<u8, u8>>(&Ok::<_, u8>(1000u64)).unwrap_err();
+        assert_eq!(err.context().path().to_string(), "(Ok).[0]");
+    }
+
+    #[test]
+    fn sequences_of_fixed_size_values_reserve_output_up_front() {
+        // Record the capacity of the output as each value is encoded.
+        struct RecordCapacity<'a>(u32, &'a core::cell::RefCell<Vec<usize>>);
+        impl EncodeAsType for RecordCapacity<'_> {
+            fn encode_as_type_to<R: TypeResolver>(
+                &self,
+                type_id: R::TypeId,
+                types: &R,
+                out: &mut Vec<u8>,
+            ) -> Result<(), Error> {
+                self.1.borrow_mut().push(out.capacity());
+                self.0.encode_as_type_to(type_id, types, out)
+            }
+        }
+
+        let (type_id, types) = make_type::<Vec<u32>>();
+        let capacities = core::cell::RefCell::new(Vec::new());
+        let values: Vec<_> = (0..1000).map(|n| RecordCapacity(n, &capacities)).collect();
+
+        let mut out = Vec::new();
+        values.encode_as_type_to(type_id, &types, &mut out).unwrap();
+        assert_eq!(out, (0..1000u32).collect::<Vec<_>>().encode());
+        assert_eq!(out.len(), 4002);
+
+        // Enough space for the length prefix and every value was reserved before any
+        // values were written, rather than the output growing as they were written:
+        let capacities = capacities.into_inner();
+        assert_eq!(capacities.len(), 1000);
+        assert!(capacities.iter().all(|&c| c >= 4002));
+    }
+
+    #[test]
+    fn boxed_values_in_options_encode_like_unboxed() {
+        assert_eq!(
+            encode_type::<_, Option<u64>>(&Some(Box::new(5u8))).unwrap(),
+            Some(5u64).encode()
+        );
+        assert_value_roundtrips_to(None::<Box<u8>>, None::<u64>);
+    }
+
+    #[test]
+    fn saturating_values_encode_as_sequence_elements() {
+        assert_eq!(
+            encode_type::<_, Vec<u64>>(&vec![Saturating(1u8), Saturating(2u8)]).unwrap(),
+            vec![1u64, 2].encode()
+        );
+        assert_value_roundtrips_to([Saturating(1u32), Saturating(2u32)], [1u8, 2]);
+    }
+
+    #[test]
+    fn btreemap_with_cow_keys_encodes_to_named_struct() {
+        use alloc::borrow::Cow;
+
+        #[derive(Debug, PartialEq, Decode, TypeInfo)]
+        struct Foo {
+            a: u64,
+            b: u16,
+        }
+
+        let map: BTreeMap<Cow<'_, str>, u8> =
+            BTreeMap::from([(Cow::Borrowed("b"), 2), (Cow::Owned("a".to_string()), 1)]);
+        assert_value_roundtrips_to(map, Foo { a: 1, b: 2 });
+    }
+
+    #[test]
+    fn boxed_str_and_slices_encode_like_their_contents() {
+        let s: Box<str> = Box::from("hi");
+        assert_value_roundtrips_to(s, "hi".to_string());
+
+        let bytes: Box<[u8]> = Box::from(vec![1u8, 2, 3]);
+        assert_value_roundtrips_to(&bytes, vec![1u8, 2, 3]);
+        assert_value_roundtrips_to(&bytes, [1u8, 2, 3]);
+        assert_value_roundtrips_to(&bytes, vec![1u64, 2, 3]);
+    }
+
+    #[test]
+    fn number_sequences_encode_like_codec() {
+        let nums: Vec<u64> = (0..100_000).collect();
+        assert_value_roundtrips_to(nums.clone(), nums.clone());
+        assert_encodes_like_codec(nums.clone());
+
+        let nums: Vec<i16> = (-500..500).collect();
+        assert_encodes_like_codec(nums.clone());
+        assert_encodes_like_codec([1i32, -2, 3, -4]);
+
+        // Different target element types still work via the slower path:
+        assert_value_roundtrips_to(vec![1u32, 2, 3], vec![1u64, 2, 3]);
+        assert_value_roundtrips_to([1u16, 2, 3], [1u128, 2, 3]);
+        assert_value_roundtrips_to(vec![1i8, -2, 3], vec![1i64, -2, 3]);
+        assert_value_roundtrips_to(
+            vec![1u64, 2, 3],
+            vec![Compact(1u64), Compact(2), Compact(3)],
+        );
+    }
+
+    #[test]
+    fn half_open_ranges_encode_as_their_endpoint() {
+        #[derive(Debug, PartialEq, Encode, Decode, TypeInfo)]
+        struct Wrapper(u32);
+
+        assert_value_roundtrips_to(5u8.., 5u64);
+        assert_value_roundtrips_to(..5u8, 5u64);
+        assert_value_roundtrips_to(5u8.., Wrapper(5));
+        assert_value_roundtrips_to(..5u8, Compact(5u32));
+        assert_value_roundtrips_to(vec![1u8.., 2u8..], vec![1u16, 2]);
+
+        let err = encode_type::<_, u8>(&(300u16..)).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::NumberOutOfRange { .. }));
+    }
+
+    #[test]
+    fn type_paths_and_docs_do_not_affect_encoding() {
+        #[allow(dead_code)]
+        mod a {
+            use super::*;
+            /// Some documentation.
+            #[derive(TypeInfo)]
+            pub struct Foo {
+                /// The first field.
+                pub a: u64,
+                pub b: bool,
+            }
+            #[derive(TypeInfo)]
+            pub enum Choice {
+                /// The first variant.
+                First(u8),
+                Second {
+                    value: u16,
+                },
+            }
+        }
+        #[allow(dead_code)]
+        mod b {
+            use super::*;
+            #[derive(TypeInfo)]
+            pub struct Bar {
+                pub a: u64,
+                pub b: bool,
+            }
+            /// Different documentation.
+            #[derive(TypeInfo)]
+            pub enum Other {
+                First(u8),
+                /// The second variant.
+                Second {
+                    value: u16,
+                },
+            }
+        }
+
+        let mut types = scale_info::Registry::new();
+        let foo_id = types
+            .register_type(&scale_info::MetaType::new::<a::Foo>())
+            .id;
+        let bar_id = types
+            .register_type(&scale_info::MetaType::new::<b::Bar>())
+            .id;
+        let choice_id = types
+            .register_type(&scale_info::MetaType::new::<a::Choice>())
+            .id;
+        let other_id = types
+            .register_type(&scale_info::MetaType::new::<b::Other>())
+            .id;
+        let types: PortableRegistry = types.into();
+
+        // Sanity check that the types really do differ in their path (docs are only
+        // retained if scale-info's "docs" feature is enabled):
+        let foo = types.resolve(foo_id).unwrap();
+        let bar = types.resolve(bar_id).unwrap();
+        assert_ne!(foo.path, bar.path);
+
+        #[derive(EncodeAsType)]
+        #[encode_as_type(crate_path = "crate")]
+        struct Value {
+            b: bool,
+            a: u8,
+        }
+        let value = Value { b: true, a: 123 };
+        assert_eq!(
+            value.encode_as_type(foo_id, &types).unwrap(),
+            value.encode_as_type(bar_id, &types).unwrap()
+        );
+        assert_eq!(
+            (123u8, true).encode_as_type(foo_id, &types).unwrap(),
+            (123u8, true).encode_as_type(bar_id, &types).unwrap()
+        );
+
+        #[derive(EncodeAsType)]
+        #[encode_as_type(crate_path = "crate")]
+        enum Choice {
+            Second { value: u8 },
+        }
+        let value = Choice::Second { value: 1 };
+        assert_eq!(
+            value.encode_as_type(choice_id, &types).unwrap(),
+            value.encode_as_type(other_id, &types).unwrap()
+        );
+        assert_eq!(
+            value.encode_as_type(choice_id, &types).unwrap(),
+            vec![1, 1, 0]
+        );
+    }
+
+    #[test]
+    fn derive_handles_lifetime_and_const_generics() {
+        #[derive(EncodeAsType)]
+        #[encode_as_type(crate_path = "crate")]
+        struct Foo<'a, T, const N: usize> {
+            data: &'a [T; N],
+        }
+
+        #[derive(EncodeAsType)]
+        #[encode_as_type(crate_path = "crate")]
+        struct Bar<'a, T, const N: usize>(&'a [T; N]);
+
+        #[derive(Debug, PartialEq, Encode, Decode, TypeInfo)]
+        struct Target {
+            data: [u64; 3],
+        }
+
+        let data = [1u8, 2, 3];
+        assert_value_roundtrips_to(Foo { data: &data }, Target { data: [1, 2, 3] });
+        assert_value_roundtrips_to(Bar(&data), Target { data: [1, 2, 3] });
+        assert_value_roundtrips_to(Bar(&data), vec![1u16, 2, 3]);
+    }
+
+    #[test]
+    fn numbers_encode_into_compact_newtypes() {
+        // This mirrors eg `Compact<Balance>` in Substrate:
+        #[derive(TypeInfo)]
+        #[allow(dead_code)]
+        struct Balance(u128);
+
+        assert_eq!(
+            encode_type::<_, Compact<Balance>>(&1000u64).unwrap(),
+            Compact(1000u128).encode()
+        );
+        assert_eq!(
+            encode_type::<_, Vec<Compact<Balance>>>(&vec![1u8, 2]).unwrap(),
+            vec![Compact(1u128), Compact(2u128)].encode()
+        );
+
+        let err = encode_type::<_, Compact<Balance>>(&-1i64).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::NumberOutOfRange { .. }));
+    }
+
+    #[test]
+    fn variants_can_ignore_trailing_fields() {
+        #[derive(EncodeAsType)]
+        #[encode_as_type(crate_path = "crate")]
+        enum Source {
+            #[encode_as_type(ignore_trailing_fields)]
+            Foo(u8, bool, String),
+            Bar(u8, bool, String),
+            Named {
+                a: u8,
+                b: bool,
+                c: String,
+            },
+        }
+
+        #[derive(Debug, PartialEq, Encode, Decode, TypeInfo)]
+        enum Target {
+            Foo(u64, bool),
+            Bar(u64, bool),
+            Named { a: u64, b: bool },
+        }
+
+        assert_value_roundtrips_to(Source::Foo(1, true, "ignored".into()), Target::Foo(1, true));
+        assert_value_roundtrips_to(
+            Source::Named {
+                a: 1,
+                b: true,
+                c: "ignored".into(),
+            },
+            Target::Named { a: 1, b: true },
+        );
+
+        // Without the attribute, the number of fields must line up:
+        let err = encode_type::<_, Target>(&Source::Bar(1, true, "nope".into())).unwrap_err();
+        assert!(matches!(
+            err.kind(),
+            ErrorKind::WrongLength {
+                actual_len: 3,
+                expected_len: 2
+            }
+        ));
+
+        // Having fewer fields than the target is still an error:
+        #[derive(TypeInfo)]
+        #[allow(dead_code)]
+        enum BiggerTarget {
+            Foo(u64, bool, String, u8),
+        }
+        let err = encode_type::<_, BiggerTarget>(&Source::Foo(1, true, "hi".into())).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::WrongLength { .. }));
+    }
+
+    #[test]
+    fn bytes_cannot_be_encoded_into_bit_sequences() {
+        use bitvec::{order::Lsb0, vec::BitVec};
+
+        let err = encode_type::<_, BitVec<u8, Lsb0>>(&vec![1u8, 0, 1]).unwrap_err();
+        assert!(matches!(
+            err.kind(),
+            ErrorKind::WrongShapeWithHint {
+                actual: Kind::Array,
+                ..
+            }
+        ));
+        assert!(err.to_string().contains("scale_bits::Bits"));
+
+        let err = encode_type::<_, BitVec<u8, Lsb0>>(&[1u8, 0, 1]).unwrap_err();
+        assert!(err.to_string().contains("scale_bits::Bits"));
+    }
+
+    #[test]
+    fn non_numeric_ranges_encode_like_tuples() {
+        // Encode isn't implemented for `char`, so compare against u32s:
+        assert_eq!(
+            encode_type::<_, (u32, u32)>(&('a'..'y')).unwrap(),
+            (97u32, 121u32).encode()
+        );
+        assert_eq!(
+            encode_type::<_, (char, char)>(&('a'..='z')).unwrap(),
+            (97u32, 122u32).encode()
+        );
+        assert_value_roundtrips_to('a'..='z', (97u64, 122u64));
+
+        let range = String::from("aardvark")..String::from("zebra");
+        assert_value_roundtrips_to(
+            range.clone(),
+            (String::from("aardvark"), String::from("zebra")),
+        );
+        assert_value_roundtrips_to(
+            "aardvark"..="zebra",
+            (String::from("aardvark"), String::from("zebra")),
+        );
+    }
+
+    #[test]
+    fn with_context_adds_location_to_errors() {
+        use crate::error::Location;
+
+        let err = encode_type::<_, u8>(&WithContext::new(1000u16, Location::field("amount")))
+            .unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::NumberOutOfRange { .. }));
+        assert_eq!(err.context().path().to_string(), "amount");
+
+        // Locations are added on top of any context from the inner value:
+        let value = WithContext::new(vec![1u16, 1000], Location::field("amounts"));
+        let err = encode_type::<_, Vec<u8>>(&value).unwrap_err();
+        assert_eq!(err.context().path().to_string(), "amounts.[1]");
+
+        // Values that encode fine are unaffected:
+        assert_value_roundtrips_to(WithContext::new(100u16, Location::idx(0)), 100u8);
+    }
+
+    #[test]
+    fn ok_only_results_encode_ok_values() {
+        #[derive(Debug)]
+        struct NotEncodable;
+
+        assert_value_roundtrips_to(OkOnly(Ok::<_, NotEncodable>(123u8)), Ok::<u64, String>(123));
+        assert_eq!(
+            encode_type::<_, Result<u64, String>>(&OkOnly(Ok::<_, NotEncodable>(123u8))).unwrap(),
+            encode_type::<_, Result<u64, String>>(&Ok::<_, String>(123u8)).unwrap()
+        );
+
+        let err =
+            encode_type::<_, Result<u64, String>>(&OkOnly(Err::<u8, _>(NotEncodable))).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::Custom(_)));
+        assert!(err.to_string().contains("only Ok values can be encoded"));
+    }
+
+    #[test]
+    fn large_arrays_encode_into_sequences() {
+        let arr = [123u32; 1000];
+        assert_value_roundtrips_to(arr, vec![123u32; 1000]);
+        assert_value_roundtrips_to(arr, vec![123u64; 1000]);
+        assert_value_roundtrips_to(arr, vec![Compact(123u32); 1000]);
+
+        let arr = [(1u8, true); 500];
+        assert_value_roundtrips_to(arr, vec![(1u16, true); 500]);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn hash_maps_encode_in_key_order() {
+        use std::collections::HashMap;
+
+        let mut map = HashMap::new();
+        for (k, v) in [("e", 5u8), ("d", 4), ("c", 3), ("b", 2), ("a", 1)] {
+            map.insert(k.to_string(), v);
+        }
+
+        // Sequences and arrays are encoded in ascending key order, like a BTreeMap:
+        assert_value_roundtrips_to(&map, vec![1u64, 2, 3, 4, 5]);
+        assert_value_roundtrips_to(&map, [1u8, 2, 3, 4, 5]);
+        assert_eq!(
+            encode_type::<_, Vec<u8>>(&map).unwrap(),
+            encode_type::<_, Vec<u8>>(&BTreeMap::from_iter(map.clone())).unwrap()
+        );
+
+        // Named structs are lined up by name:
+        #[derive(Debug, PartialEq, Encode, Decode, TypeInfo)]
+        struct Foo {
+            d: u16,
+            b: u32,
+        }
+        assert_value_roundtrips_to(&map, Foo { d: 4, b: 2 });
+
+        let err = encode_type::<_, Foo>(&HashMap::from([("d", 1u8)])).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::CannotFindField { name } if name == "b"));
+
+        // Entries are ordered by key rather than by the string that each key gives back:
+        #[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Clone)]
+        struct ReverseKey(core::cmp::Reverse<&'static str>);
+        impl AsRef<str> for ReverseKey {
+            fn as_ref(&self) -> &str {
+                self.0 .0
+            }
+        }
+        let map = HashMap::from(
+            [("a", 1u8), ("b", 2), ("c", 3)].map(|(k, v)| (ReverseKey(core::cmp::Reverse(k)), v)),
+        );
+        assert_value_roundtrips_to(&map, vec![3u8, 2, 1]);
+        assert_eq!(
+            encode_type::<_, Vec<u8>>(&map).unwrap(),
+            encode_type::<_, Vec<u8>>(&BTreeMap::from_iter(map.clone())).unwrap()
+        );
+    }
+
+    #[test]
+    fn variants_can_try_alternative_names() {
+        #[derive(Debug, PartialEq, Encode, Decode, TypeInfo)]
+        enum Target {
+            Foo(u8),
+            Bar(u8),
+            Wibble(u8),
+        }
+
+        let (type_id, types) = make_type::<Target>();
+        let encode = |name, alternatives| {
+            Variant {
+                name,
+                fields: Composite::new([(None, CompositeField::new(&1u8))].iter().copied()),
+            }
+            .encode_variant_as_type_with_alternatives(alternatives, type_id, &types)
+        };
+
+        // The first name is absent, so the second is used:
+        assert_eq!(
+            encode("Missing", &["Bar", "Wibble"]).unwrap(),
+            Target::Bar(1).encode()
+        );
+        // Earlier names are preferred, regardless of the order of variants in the target:
+        assert_eq!(
+            encode("Missing", &["Wibble", "Foo"]).unwrap(),
+            Target::Wibble(1).encode()
+        );
+        assert_eq!(encode("Bar", &["Foo"]).unwrap(), Target::Bar(1).encode());
+
+        // If none exist, each of the names tried is reported:
+        let err = encode("Missing", &["AlsoMissing"]).unwrap_err();
+        assert!(matches!(
+            err.kind(),
+            ErrorKind::CannotFindVariant { name, .. } if name == "Missing or AlsoMissing"
+        ));
+
+        // Errors in the fields point at the target variant that was chosen:
+        let err = Variant {
+            name: "Missing",
+            fields: Composite::new([(None, CompositeField::new(&300u16))].iter().copied()),
+        }
+        .encode_variant_as_type_with_alternatives(&["Bar"], type_id, &types)
+        .unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::NumberOutOfRange { .. }));
+        assert_eq!(err.context().path().to_string(), "(Bar).[0]");
+    }
+
+    #[test]
+    fn encode_as_type_or_tries_each_type_id() {
+        use crate::util::encode_as_type_or;
+
+        #[derive(TypeInfo)]
+        #[allow(dead_code)]
+        struct Old {
+            flag: bool,
+        }
+        #[derive(TypeInfo)]
+        #[allow(dead_code)]
+        struct New {
+            amount: u64,
+        }
+
+        let (type_id, types) = make_type::<(Old, New)>();
+        let scale_info::TypeDef::Tuple(t) = &types.resolve(type_id).unwrap().type_def else {
+            panic!("Expected tuple type def");
+        };
+        let (old_id, new_id) = (t.fields[0].id, t.fields[1].id);
+
+        // The first ID is incompatible, so the second is used:
+        let bytes = encode_as_type_or(&1000u16, &[old_id, new_id], &types).unwrap();
+        assert_eq!(bytes, 1000u64.encode());
+
+        // The first compatible ID is used:
+        let bytes = encode_as_type_or(&true, &[old_id, new_id], &types).unwrap();
+        assert_eq!(bytes, true.encode());
+
+        // The last error is returned if nothing is compatible:
+        let err = encode_as_type_or(&-1i8, &[new_id, old_id], &types).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::WrongShape { .. }));
+        let err = encode_as_type_or(&-1i8, &[old_id, new_id], &types).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::NumberOutOfRange { .. }));
+
+        // And an error if no IDs are given:
+        encode_as_type_or(&1u8, &[], &types).unwrap_err();
+    }
+
+    #[test]
+    fn options_can_encode_as_sequences() {
+        let some = OptionAsSeq(Some(1u8));
+        let none = OptionAsSeq(None::<u8>);
+
+        assert_value_roundtrips_to(some, vec![1u64]);
+        assert_value_roundtrips_to(none, Vec::<u64>::new());
+        assert_value_roundtrips_to(some, [1u16; 1]);
+        assert_value_roundtrips_to(none, [0u16; 0]);
+
+        // Single field wrappers around sequences are fine too:
+        #[derive(Debug, PartialEq, Encode, Decode, TypeInfo)]
+        struct BoundedVec(Vec<u32>);
+        assert_value_roundtrips_to(some, BoundedVec(vec![1]));
+        assert_value_roundtrips_to(none, BoundedVec(vec![]));
+
+        // Arrays must have a matching length:
+        let err = encode_type::<_, [u16; 2]>(&some).unwrap_err();
+        assert!(matches!(
+            err.kind(),
+            ErrorKind::WrongLength {
+                actual_len: 1,
+                expected_len: 2
+            }
+        ));
+        let err = encode_type::<_, [u16; 1]>(&none).unwrap_err();
+        assert!(matches!(
+            err.kind(),
+            ErrorKind::WrongLength {
+                actual_len: 0,
+                expected_len: 1
+            }
+        ));
+
+        // Errors in the value point at its index:
+        let err = encode_type::<_, Vec<u8>>(&OptionAsSeq(Some(1000u16))).unwrap_err();
+        assert_eq!(err.context().path().to_string(), "[0]");
+    }
+
+    #[test]
+    fn nested_sequences_have_nested_index_context() {
+        let value = vec![vec![1u8, 2], vec![3u8]];
+        assert_eq!(
+            encode_type::<_, Vec<Vec<u64>>>(&value).unwrap(),
+            vec![vec![1u64, 2], vec![3u64]].encode()
+        );
+        assert_value_roundtrips_to(value, vec![vec![1u64, 2], vec![3u64]]);
+
+        let value = vec![vec![1u16, 2], vec![3u16, 4, 1000]];
+        let err = encode_type::<_, Vec<Vec<u8>>>(&value).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::NumberOutOfRange { .. }));
+        assert_eq!(err.context().path().to_string(), "[1].[2]");
+        let idxs: Vec<_> = err
+            .context()
+            .locations()
+            .map(|l| l.as_idx().unwrap())
+            .collect();
+        assert_eq!(idxs, vec![1, 2]);
+    }
+
+    #[test]
+    fn fieldless_enums_can_encode_as_discriminants() {
+        #[derive(Debug, PartialEq, Encode, Decode, TypeInfo, EncodeAsType)]
+        #[encode_as_type(crate_path = "crate", discriminant)]
+        enum Status {
+            Active,
+            Paused = 5,
+            Stopped,
+        }
+
+        // Numeric targets get the discriminant:
+        assert_value_roundtrips_to(Status::Active, 0u8);
+        assert_value_roundtrips_to(Status::Paused, 5u8);
+        assert_value_roundtrips_to(Status::Stopped, 6u64);
+        assert_value_roundtrips_to(Status::Stopped, Compact(6u32));
+
+        // Variant targets are still encoded by name:
+        assert_encodes_like_codec(Status::Paused);
+        assert_encodes_like_codec(Status::Stopped);
+
+        // And numbers must still fit:
+        #[derive(EncodeAsType)]
+        #[encode_as_type(crate_path = "crate", discriminant)]
+        enum Big {
+            A = 1000,
+        }
+        let err = encode_type::<_, u8>(&Big::A).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::NumberOutOfRange { .. }));
+        assert_eq!(err.context().path().to_string(), "(A)");
+
+        // Discriminants outside of the range of a u32 are fine too:
+        const BASE: u64 = 0x1_0000_0000;
+        #[derive(EncodeAsType)]
+        #[encode_as_type(crate_path = "crate", discriminant)]
+        #[repr(i64)]
+        #[allow(dead_code)]
+        enum Wide {
+            Negative = -1,
+            Zero,
+            Large = 0x1_0000_0002,
+            Const = BASE as i64 + 5,
+        }
+        assert_value_roundtrips_to(Wide::Negative, -1i8);
+        assert_value_roundtrips_to(Wide::Zero, 0u8);
+        assert_value_roundtrips_to(Wide::Large, 0x1_0000_0002u64);
+        assert_value_roundtrips_to(Wide::Const, 0x1_0000_0005i128);
+        let err = encode_type::<_, u32>(&Wide::Large).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::NumberOutOfRange { .. }));
+        let err = encode_type::<_, u8>(&Wide::Negative).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::NumberOutOfRange { .. }));
+    }
+
+    #[test]
+    fn variants_can_encode_as_discriminants() {
+        let variant = Variant {
+            name: "Foo",
+            fields: Composite::new([].iter().copied()),
+        };
+        let (type_id, types) = make_type::<u16>();
+        let bytes = variant
+            .encode_variant_or_discriminant_as_type(3, type_id, &types)
+            .unwrap();
+        assert_eq!(bytes, 3u16.encode());
+
+        #[derive(Encode, TypeInfo)]
+        #[allow(dead_code)]
+        enum Target {
+            Bar,
+            Foo,
+        }
+        let (type_id, types) = make_type::<Target>();
+        let bytes = variant
+            .encode_variant_or_discriminant_as_type(3, type_id, &types)
+            .unwrap();
+        assert_eq!(bytes, Target::Foo.encode());
+
+        // Variants with fields can't be encoded as a discriminant:
+        let variant = Variant {
+            name: "Foo",
+            fields: Composite::new([(None, CompositeField::new(&1u8))].into_iter()),
+        };
+        let (type_id, types) = make_type::<u16>();
+        variant
+            .encode_variant_or_discriminant_as_type(3, type_id, &types)
+            .unwrap_err();
+    }
+
+    #[test]
+    fn seq_lengths_must_match_their_iterators() {
+        assert_value_roundtrips_to(Seq(3, [1u8, 2, 3].iter()), vec![1u64, 2, 3]);
+        assert_value_roundtrips_to(Seq(3, 1u8..=3), [1u16, 2, 3]);
+        assert_value_roundtrips_to(Seq(0, core::iter::empty::<u8>()), Vec::<u8>::new());
+
+        // Too few or too many items is an error:
+        let err = encode_type::<_, Vec<u64>>(&Seq(4, 1u8..=3)).unwrap_err();
+        assert!(matches!(
+            err.kind(),
+            ErrorKind::WrongLength {
+                actual_len: 3,
+                expected_len: 4
+            }
+        ));
+        let err = encode_type::<_, Vec<u64>>(&Seq(2, 1u8..=3)).unwrap_err();
+        assert!(matches!(
+            err.kind(),
+            ErrorKind::WrongLength {
+                actual_len: 3,
+                expected_len: 2
+            }
+        ));
+
+        // Unbounded iterators stop being consumed once they've produced too many items:
+        let err = encode_type::<_, [u8; 3]>(&Seq(3, 0u8..)).unwrap_err();
+        assert!(matches!(
+            err.kind(),
+            ErrorKind::WrongLength {
+                actual_len: 4,
+                expected_len: 3
+            }
+        ));
+        let err = encode_type::<_, Vec<u64>>(&Seq(3, core::iter::repeat(1u8))).unwrap_err();
+        assert!(matches!(
+            err.kind(),
+            ErrorKind::WrongLength {
+                actual_len: 4,
+                expected_len: 3
+            }
+        ));
+
+        // Nothing is left in the output when this happens:
+        let (type_id, types) = make_type::<Vec<u64>>();
+        let mut out = vec![1, 2, 3];
+        Seq(2, 1u8..=3)
+            .encode_as_type_to(type_id, &types, &mut out)
+            .unwrap_err();
+        assert_eq!(out, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn strings_encode_into_char_sequences() {
+        // Encode isn't implemented for `char`, so compare against u32s:
+        assert_eq!(
+            encode_type::<_, Vec<char>>(&"abc").unwrap(),
+            vec![97u32, 98, 99].encode()
+        );
+        assert_eq!(
+            encode_type::<_, Vec<char>>(&String::from("a😀")).unwrap(),
+            vec!['a' as u32, '😀' as u32].encode()
+        );
+        assert_eq!(
+            encode_type::<_, [char; 3]>(&"abc").unwrap(),
+            [97u32, 98, 99].encode()
+        );
+
+        // Arrays must be the right length:
+        let err = encode_type::<_, [char; 2]>(&"abc").unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::WrongLength { .. }));
+
+        // Other sequences aren't allowed:
+        let err = encode_type::<_, Vec<u32>>(&"abc").unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::WrongShape { .. }));
+        let err = encode_type::<_, Vec<u8>>(&"abc").unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::WrongShape { .. }));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn hash_sets_encode_like_btree_sets() {
+        use std::collections::HashSet;
+
+        let set: HashSet<u8> = [5, 4, 3, 2, 1].into_iter().collect();
+        assert_value_roundtrips_to(&set, vec![1u64, 2, 3, 4, 5]);
+        assert_value_roundtrips_to(&set, [1u8, 2, 3, 4, 5]);
+        assert_eq!(
+            encode_type::<_, Vec<u32>>(&set).unwrap(),
+            encode_type::<_, Vec<u32>>(&BTreeSet::from_iter(set.clone())).unwrap()
+        );
+
+        #[derive(TypeInfo)]
+        #[allow(dead_code)]
+        struct Foo {
+            a: u8,
+            b: u8,
+        }
+        let err = encode_type::<_, Foo>(&set).unwrap_err();
+        assert!(matches!(
+            err.kind(),
+            ErrorKind::WrongShapeWithHint {
+                actual: Kind::Array,
+                ..
+            }
+        ));
+        assert!(err
+            .to_string()
+            .contains("a set cannot be encoded as a named struct"));
+    }
+
+    #[test]
+    fn btreemaps_with_non_string_keys_encode_by_position() {
+        #[derive(TypeInfo, Encode)]
+        struct Positional(u8, u64, u32);
+        #[derive(TypeInfo, Encode)]
+        struct Named {
+            a: u8,
+            b: u64,
+        }
+
+        // Values are encoded in key order, regardless of insertion order:
+        let map = BTreeMap::from([(2u32, 300u16), (1, 100), (3, 1)]);
+        assert_eq!(
+            encode_type::<_, (u8, u64, u32)>(&ByKeyOrder(&map)).unwrap(),
+            (100u8, 300u64, 1u32).encode()
+        );
+        assert_value_roundtrips_to(ByKeyOrder(&map), vec![100u16, 300, 1]);
+        assert_value_roundtrips_to(ByKeyOrder(&map), [100u64, 300, 1]);
+
+        assert_encodes_fields_like_type(ByKeyOrder(&map), Positional(100, 300, 1));
+        assert_eq!(
+            encode_type::<_, Positional>(&ByKeyOrder(&map)).unwrap(),
+            Positional(100, 300, 1).encode()
+        );
+
+        // Named fields can't be lined up with the keys:
+        let map = BTreeMap::from([(1u32, 1u8), (2, 2)]);
+        let err = encode_type::<_, Named>(&ByKeyOrder(&map)).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::CannotFindField { name } if name == "a"));
+    }
+
+    #[test]
+    fn btreemaps_with_any_string_like_keys_encode_by_name() {
+        #[derive(PartialEq, Eq, PartialOrd, Ord)]
+        struct MyKey(String);
+        impl AsRef<str> for MyKey {
+            fn as_ref(&self) -> &str {
+                &self.0
+            }
+        }
+
+        #[derive(Debug, PartialEq, TypeInfo, Decode)]
+        struct Foo {
+            a: u8,
+            b: u64,
+        }
+
+        let map = BTreeMap::from([(MyKey("b".into()), 2u8), (MyKey("a".into()), 1)]);
+        assert_value_roundtrips_to(map, Foo { a: 1, b: 2 });
+    }
+
+    #[test]
+    fn usize_and_isize_encode_independently_of_platform_width() {
+        // Values that fit into the target encode exactly like a fixed width number
+        // would, whatever the width of usize/isize is on this platform:
+        for n in [0usize, 1, 255, 65_535, u32::MAX as usize] {
+            let as_u32 = n as u32;
+            assert_eq!(encode_type::<_, u32>(&n).unwrap(), as_u32.encode());
+            assert_eq!(encode_type::<_, u64>(&n).unwrap(), (as_u32 as u64).encode());
+            assert_eq!(
+                encode_type::<_, u128>(&n).unwrap(),
+                (as_u32 as u128).encode()
+            );
+            assert_eq!(
+                encode_type::<_, Compact<u64>>(&n).unwrap(),
+                Compact(as_u32 as u64).encode()
+            );
+        }
+        for n in [0isize, -1, 127, -128, i32::MIN as isize, i32::MAX as isize] {
+            let as_i32 = n as i32;
+            assert_eq!(encode_type::<_, i32>(&n).unwrap(), as_i32.encode());
+            assert_eq!(encode_type::<_, i64>(&n).unwrap(), (as_i32 as i64).encode());
+            assert_eq!(
+                encode_type::<_, i128>(&n).unwrap(),
+                (as_i32 as i128).encode()
+            );
+        }
+
+        // Values that don't fit into the target error, rather than being truncated:
+        let err = encode_type::<_, u8>(&256usize).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::NumberOutOfRange { .. }));
+        let err = encode_type::<_, u16>(&65_536usize).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::NumberOutOfRange { .. }));
+        let err = encode_type::<_, i8>(&-129isize).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::NumberOutOfRange { .. }));
+        let err = encode_type::<_, u32>(&-1isize).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::NumberOutOfRange { .. }));
+        #[cfg(target_pointer_width = "64")]
+        {
+            let err = encode_type::<_, u32>(&(u32::MAX as usize + 1)).unwrap_err();
+            assert!(matches!(err.kind(), ErrorKind::NumberOutOfRange { .. }));
+            let err = encode_type::<_, i32>(&(i32::MIN as isize - 1)).unwrap_err();
+            assert!(matches!(err.kind(), ErrorKind::NumberOutOfRange { .. }));
+        }
+    }
+
+    #[test]
+    fn large_tuples_encode_like_tuple_structs() {
+        #[derive(TypeInfo, Encode)]
+        struct TwentyFour(
+            u8,
+            u16,
+            u32,
+            u64,
+            u128,
+            i8,
+            i16,
+            i32,
+            i64,
+            i128,
+            bool,
+            String,
+            u8,
+            u16,
+            u32,
+            u64,
+            u128,
+            i8,
+            i16,
+            i32,
+            i64,
+            i128,
+            bool,
+            String,
+        );
+
+        let value = (
+            1u8, 2u16, 3u32, 4u64, 5u128, -6i8, -7i16, -8i32, -9i64, -10i128, true, "a", 11u8,
+            12u16, 13u32, 14u64, 15u128, -16i8, -17i16, -18i32, -19i64, -20i128, false, "b",
+        );
+        let target = TwentyFour(
+            1,
+            2,
+            3,
+            4,
+            5,
+            -6,
+            -7,
+            -8,
+            -9,
+            -10,
+            true,
+            "a".to_string(),
+            11,
+            12,
+            13,
+            14,
+            15,
+            -16,
+            -17,
+            -18,
+            -19,
+            -20,
+            false,
+            "b".to_string(),
+        );
+        assert_eq!(
+            encode_type::<_, TwentyFour>(&value).unwrap(),
+            target.encode()
+        );
+
+        // The smallest tuples are still handled too:
+        assert_encodes_like_codec(());
+        assert_encodes_like_codec((1u8,));
+    }
+
+    #[test]
+    fn renamed_fields_line_up_with_target_names() {
+        #[derive(EncodeAsType)]
+        #[encode_as_type(crate_path = "crate")]
+        struct Foo {
+            #[encode_as_type(rename = "dest")]
+            to: u8,
+            #[encode_as_type(rename = "someValue")]
+            some_value: bool,
+        }
+
+        #[derive(EncodeAsType)]
+        #[encode_as_type(crate_path = "crate")]
+        enum Bar {
+            A {
+                #[encode_as_type(rename = "dest")]
+                to: u8,
+            },
+        }
+
+        #[derive(TypeInfo, Encode)]
+        #[allow(non_snake_case)]
+        struct FooTarget {
+            someValue: bool,
+            dest: u64,
+        }
+
+        #[derive(TypeInfo, Encode)]
+        enum BarTarget {
+            A { dest: u64 },
+        }
+
+        let foo = Foo {
+            to: 1,
+            some_value: true,
+        };
+        let target = FooTarget {
+            someValue: true,
+            dest: 1,
+        };
+        assert_eq!(encode_type::<_, FooTarget>(&foo).unwrap(), target.encode());
+        assert_eq!(
+            encode_type::<_, BarTarget>(&Bar::A { to: 2 }).unwrap(),
+            BarTarget::A { dest: 2 }.encode()
+        );
+
+        // The Rust field name is no longer used:
+        #[derive(TypeInfo, Encode)]
+        struct OtherTarget {
+            to: u64,
+        }
+        let foo = Foo {
+            to: 1,
+            some_value: true,
+        };
+        let err = encode_type::<_, OtherTarget>(&foo).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::CannotFindField { name } if name == "to"));
+    }
+
+    #[test]
+    fn sequence_target_encodes_like_per_call_path() {
+        #[derive(TypeInfo, Encode)]
+        struct Wrapper(Vec<u64>);
+
+        fn check<T: TypeInfo + 'static>(columns: &[Vec<u8>]) {
+            let (type_id, types) = make_type::<T>();
+            let target = SequenceTarget::new(type_id, &types).unwrap();
+            for values in columns {
+                let bytes = target.encode_iter(values, &types).unwrap();
+                assert_eq!(bytes, values.encode_as_type(type_id, &types).unwrap());
+            }
+        }
+
+        let columns = [vec![1u8, 2, 3], vec![], vec![4, 5, 6]];
+        check::<Vec<u64>>(&columns);
+        check::<Vec<Compact<u32>>>(&columns);
+        check::<Wrapper>(&columns);
+        check::<(Vec<u64>,)>(&columns);
+        check::<[u16; 3]>(&[vec![1, 2, 3], vec![4, 5, 6]]);
+
+        // Arrays have a fixed length:
+        let (type_id, types) = make_type::<[u16; 3]>();
+        let target = SequenceTarget::new(type_id, &types).unwrap();
+        assert_eq!(target.array_len(), Some(3));
+        let err = target.encode_iter([1u8, 2], &types).unwrap_err();
+        assert!(matches!(
+            err.kind(),
+            ErrorKind::WrongLength {
+                actual_len: 2,
+                expected_len: 3
+            }
+        ));
+
+        // Errors point to the value that failed to encode:
+        let (type_id, types) = make_type::<Vec<u8>>();
+        let target = SequenceTarget::new(type_id, &types).unwrap();
+        assert_eq!(target.array_len(), None);
+        let err = target.encode_iter([1u16, 256], &types).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::NumberOutOfRange { .. }));
+        assert_eq!(err.context().path().to_string(), "[1]");
+
+        // Types which aren't sequences or arrays are rejected up front:
+        let (type_id, types) = make_type::<(u8, u8)>();
+        let err = SequenceTarget::new(type_id, &types).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::WrongShape { .. }));
+    }
+
+    #[test]
+    fn renamed_variants_line_up_with_target_names() {
+        #[derive(EncodeAsType)]
+        #[encode_as_type(crate_path = "crate")]
+        enum Foo {
+            #[encode_as_type(rename = "VariantA")]
+            A,
+            #[encode_as_type(rename = "VariantB")]
+            B(u8),
+            C {
+                value: u8,
+            },
+        }
+
+        #[derive(TypeInfo, Encode)]
+        enum FooTarget {
+            C { value: u64 },
+            VariantB(u32),
+            VariantA,
+        }
+
+        assert_eq!(
+            encode_type::<_, FooTarget>(&Foo::A).unwrap(),
+            FooTarget::VariantA.encode()
+        );
+        assert_eq!(
+            encode_type::<_, FooTarget>(&Foo::B(1)).unwrap(),
+            FooTarget::VariantB(1).encode()
+        );
+        assert_eq!(
+            encode_type::<_, FooTarget>(&Foo::C { value: 2 }).unwrap(),
+            FooTarget::C { value: 2 }.encode()
+        );
+
+        // The Rust variant name is no longer used:
+        #[derive(TypeInfo, Encode)]
+        #[allow(dead_code)]
+        enum OtherTarget {
+            A,
+        }
+        let err = encode_type::<_, OtherTarget>(&Foo::A).unwrap_err();
+        assert!(
+            matches!(err.kind(), ErrorKind::CannotFindVariant { name, .. } if name == "VariantA")
+        );
+    }
+
+    #[test]
+    fn ip_addrs_encode_by_variant_name_or_index() {
+        let v4 = Ipv4Addr::new(127, 0, 0, 1);
+        let v6 = Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 1);
+
+        assert_value_roundtrips_to(v4, v4.octets());
+
+        // Variants named like std's IpAddr are found by name, whatever their index:
+        #[derive(Debug, TypeInfo, Encode, Decode, PartialEq)]
+        enum Named {
+            V6([u8; 16]),
+            Other,
+            V4([u8; 4]),
+        }
+        assert_value_roundtrips_to(IpAddr::V4(v4), Named::V4(v4.octets()));
+        assert_value_roundtrips_to(IpAddr::V6(v6), Named::V6(v6.octets()));
+
+        // Otherwise we fall back to the variant indexes:
+        #[derive(Debug, TypeInfo, Encode, Decode, PartialEq)]
+        enum Indexed {
+            Ipv4([u8; 4]),
+            Ipv6([u16; 8]),
+        }
+        assert_value_roundtrips_to(IpAddr::V4(v4), Indexed::Ipv4(v4.octets()));
+        assert_value_roundtrips_to(IpAddr::V6(v6), Indexed::Ipv6(v6.segments()));
+    }
+
+    #[test]
+    fn fields_can_be_skipped_conditionally() {
+        #[derive(EncodeAsType)]
+        #[encode_as_type(crate_path = "crate")]
+        struct Foo {
+            a: u8,
+            #[encode_as_type(skip_if = "Option::is_none")]
+            b: Option<u8>,
+        }
+
+        #[derive(TypeInfo, Encode)]
+        struct Old {
+            a: u64,
+        }
+        #[derive(TypeInfo, Encode)]
+        struct New {
+            a: u64,
+            b: Option<u64>,
+        }
+
+        // When skipped, the field doesn't need to exist in the target:
+        let foo = Foo { a: 1, b: None };
+        assert_eq!(encode_type::<_, Old>(&foo).unwrap(), Old { a: 1 }.encode());
+
+        // Otherwise it's encoded as normal:
+        let foo = Foo { a: 1, b: Some(2) };
+        assert_eq!(
+            encode_type::<_, New>(&foo).unwrap(),
+            New { a: 1, b: Some(2) }.encode()
+        );
+
+        // If a target needs the skipped field, it can't be found:
+        let foo = Foo { a: 1, b: None };
+        let err = encode_type::<_, New>(&foo).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::CannotFindField { name } if name == "b"));
+
+        // Unnamed targets would be lined up by position, which we refuse to do once a
+        // field has been skipped:
+        #[derive(EncodeAsType)]
+        #[encode_as_type(crate_path = "crate")]
+        struct Bar {
+            a: u8,
+            #[encode_as_type(skip_if = "Option::is_none")]
+            b: Option<u8>,
+            c: bool,
+        }
+        let bar = Bar {
+            a: 1,
+            b: None,
+            c: true,
+        };
+        let err = encode_type::<_, (u64, Option<u64>, bool)>(&bar).unwrap_err();
+        assert!(matches!(
+            err.kind(),
+            ErrorKind::CannotLineUpFieldsByPosition
+        ));
+
+        // This is the case even if the remaining number of fields happens to line up:
+        let bar = Bar {
+            a: 1,
+            b: None,
+            c: true,
+        };
+        let err = encode_type::<_, (u64, bool)>(&bar).unwrap_err();
+        assert!(matches!(
+            err.kind(),
+            ErrorKind::CannotLineUpFieldsByPosition
+        ));
+
+        // If nothing is skipped, fields can be lined up by position as normal:
+        let bar = Bar {
+            a: 1,
+            b: Some(2),
+            c: true,
+        };
+        assert_eq!(
+            encode_type::<_, (u64, Option<u64>, bool)>(&bar).unwrap(),
+            (1u64, Some(2u64), true).encode()
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn all_errors_can_be_collected() {
+        #[derive(EncodeAsType)]
+        #[encode_as_type(crate_path = "crate")]
+        struct Foo {
+            a: u16,
+            b: Bar,
+            c: Vec<u16>,
+        }
+        #[derive(EncodeAsType)]
+        #[encode_as_type(crate_path = "crate")]
+        enum Bar {
+            X { value: u16 },
+        }
+
+        #[derive(TypeInfo, Encode)]
+        struct FooTarget {
+            a: u8,
+            b: BarTarget,
+            c: Vec<u8>,
+        }
+        #[derive(TypeInfo, Encode)]
+        enum BarTarget {
+            X { value: u8 },
+        }
+
+        let (type_id, types) = make_type::<FooTarget>();
+
+        // Every field that fails is reported, with the path to it:
+        let foo = Foo {
+            a: 256,
+            b: Bar::X { value: 1000 },
+            c: vec![1, 300, 2, 400],
+        };
+        let errs = foo
+            .encode_as_type_collect_errors(type_id, &types)
+            .unwrap_err();
+        let paths: Vec<_> = errs
+            .iter()
+            .map(|e| e.context().path().to_string())
+            .collect();
+        assert_eq!(paths, vec!["a", "b.(X).value", "c.[1]", "c.[3]"]);
+        assert!(errs
+            .iter()
+            .all(|e| matches!(e.kind(), ErrorKind::NumberOutOfRange { .. })));
+
+        // If nothing fails, we get the bytes back as normal:
+        let foo = Foo {
+            a: 1,
+            b: Bar::X { value: 2 },
+            c: vec![3],
+        };
+        let target = FooTarget {
+            a: 1,
+            b: BarTarget::X { value: 2 },
+            c: vec![3],
+        };
+        assert_eq!(
+            foo.encode_as_type_collect_errors(type_id, &types).unwrap(),
+            target.encode()
+        );
+
+        // Errors which aren't in some nested value are reported too:
+        let errs = 1u8
+            .encode_as_type_collect_errors(type_id, &types)
+            .unwrap_err();
+        assert_eq!(errs.len(), 1);
+
+        // Errors are no longer collected afterwards:
+        let foo = Foo {
+            a: 256,
+            b: Bar::X { value: 1000 },
+            c: vec![],
+        };
+        let err = foo.encode_as_type(type_id, &types).unwrap_err();
+        assert_eq!(err.context().path().to_string(), "a");
+    }
+
+    #[test]
+    fn cow_impls_cover_common_cases() {
+        use alloc::borrow::Cow;
+
+        #[derive(EncodeAsType, Encode, Decode, TypeInfo, Clone, PartialEq, Debug)]
+        #[encode_as_type(crate_path = "crate")]
+        struct Custom {
+            a: u8,
+        }
+
+        fn is_encode_as_type<T: EncodeAsType + ?Sized>() {}
+        is_encode_as_type::<Cow<'_, str>>();
+        is_encode_as_type::<Cow<'_, [u8]>>();
+        is_encode_as_type::<Cow<'_, Custom>>();
+        is_encode_as_type::<Cow<'_, Cow<'_, str>>>();
+        is_encode_as_type::<Cow<'_, Cow<'_, [u8]>>>();
+
+        let s: Cow<'_, str> = Cow::Borrowed("hi");
+        assert_encodes_like_codec(s.clone());
+        assert_encodes_like_codec(Cow::<Cow<'_, str>>::Owned(s));
+        assert_value_roundtrips_to(Cow::<[u8]>::Owned(vec![1, 2]), vec![1u8, 2]);
+        assert_value_roundtrips_to(
+            Cow::<Cow<'_, [u8]>>::Owned(Cow::Borrowed(&[3u8])),
+            vec![3u8],
+        );
+
+        let custom = Custom { a: 1 };
+        assert_value_roundtrips_to(Cow::Borrowed(&custom), custom.clone());
+        assert_value_roundtrips_to(Cow::<Custom>::Owned(custom.clone()), custom);
+    }
+
+    #[test]
+    fn numbers_encode_into_wrappers_around_compacts() {
+        // The wrapper is peeled off first, and then the number is encoded as a compact.
+        #[derive(Encode, TypeInfo)]
+        struct Fee(Compact<u128>);
+
+        #[derive(Encode, TypeInfo)]
+        struct Fees {
+            fee: Fee,
+        }
+
+        assert_eq!(
+            encode_type::<_, Fee>(&1000u64).unwrap(),
+            Fee(Compact(1000)).encode()
+        );
+        assert_eq!(
+            encode_type::<_, Fees>(&1000u64).unwrap(),
+            Compact(1000u128).encode()
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn fallbacks_see_errors_when_error_collection_is_suspended() {
+        #[derive(EncodeAsType, TypeInfo, Encode)]
+        #[encode_as_type(crate_path = "crate")]
+        struct Wrapper<T> {
+            value: T,
+        }
+
+        // Encodes the value if it fits, and the largest value that fits otherwise.
+        struct Saturating(u16);
+        impl EncodeAsType for Saturating {
+            fn encode_as_type_to<R: TypeResolver>(
+                &self,
+                type_id: R::TypeId,
+                types: &R,
+                out: &mut Vec<u8>,
+            ) -> Result<(), Error> {
+                let res = crate::error::suspend_error_collection(|| {
+                    Wrapper { value: self.0 }.encode_as_type_to(type_id.clone(), types, out)
+                });
+                match res {
+                    Ok(()) => Ok(()),
+                    Err(_) => Wrapper { value: u8::MAX }.encode_as_type_to(type_id, types, out),
+                }
+            }
+        }
+
+        #[derive(TypeInfo, Encode)]
+        struct Target {
+            a: Wrapper<u8>,
+            b: u8,
+        }
+
+        let (type_id, types) = make_type::<Target>();
+        let value = (Saturating(1000), 2u8);
+        let bytes = value
+            .encode_as_type_collect_errors(type_id, &types)
+            .unwrap();
+        let expected = Target {
+            a: Wrapper { value: 255 },
+            b: 2,
+        };
+        assert_eq!(bytes, expected.encode());
+    }
+
+    #[test]
+    fn attrs_are_used_alongside_codec_attrs() {
+        #[derive(EncodeAsType, Encode)]
+        #[encode_as_type(crate_path = "crate")]
+        enum Foo {
+            #[codec(index = 0)]
+            #[encode_as_type(rename = "VariantA")]
+            A(u8),
+        }
+
+        #[derive(EncodeAsType, Encode)]
+        #[encode_as_type(crate_path = "crate")]
+        struct Bar {
+            #[codec(compact)]
+            #[encode_as_type(rename = "b")]
+            a: u64,
+            #[codec(skip)]
+            c: bool,
+        }
+
+        #[derive(Debug, PartialEq, Decode, TypeInfo)]
+        enum FooTarget {
+            VariantA(u8),
+        }
+
+        #[derive(Debug, PartialEq, Decode, TypeInfo)]
+        struct BarTarget {
+            b: u64,
+        }
+
+        assert_value_roundtrips_to(Foo::A(1), FooTarget::VariantA(1));
+        assert_value_roundtrips_to(Bar { a: 2, c: true }, BarTarget { b: 2 });
+    }
+
+    #[test]
+    fn fixed_encoded_size_is_none_if_it_would_overflow() {
+        use crate::testing::MockResolver;
+        use pre_encoded::fixed_encoded_size;
+
+        let big = u32::MAX as usize;
+        let types = MockResolver::new()
+            .add_primitive(0, Primitive::U64)
+            .add_array(1, 0, big)
+            .add_array(2, 1, big)
+            .add_array(3, 0, usize::MAX / 8)
+            .add_tuple(4, [3, 3])
+            .add_composite(5, [(Some("a"), 3), (Some("b"), 0)]);
+
+        assert_eq!(fixed_encoded_size(1, &types).unwrap(), Some(8 * big));
+        assert_eq!(fixed_encoded_size(2, &types).unwrap(), None);
+        assert_eq!(fixed_encoded_size(4, &types).unwrap(), None);
+        assert_eq!(fixed_encoded_size(5, &types).unwrap(), None);
+
+        let err = CheckedPreEncoded(&[1, 2, 3])
+            .encode_as_type(2, &types)
+            .unwrap_err();
+        assert!(err.to_string().contains("is not fixed or is too large"));
+    }
+
+    #[test]
+    fn huge_target_types_dont_cause_huge_reservations() {
+        use crate::testing::MockResolver;
+
+        let types = MockResolver::new()
+            .add_primitive(0, Primitive::U64)
+            .add_array(1, 0, u32::MAX as usize)
+            .add_sequence(2, 1);
+        let values = vec![vec![1u8]; 1000];
+
+        // We'd run out of memory if we reserved space for each value up front:
+        let err = values.encode_as_type(2, &types).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::WrongLength { .. }));
+
+        let target = SequenceTarget::new(2, &types).unwrap();
+        let err = target.encode_iter(values.iter(), &types).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::WrongLength { .. }));
+    }
+
+    #[test]
+    fn sequence_targets_dont_recurse_forever_into_wrappers() {
+        use crate::testing::MockResolver;
+
+        // Each of these types wraps the other:
+        let types = MockResolver::new()
+            .add_composite(0, [(Some("a"), 1)])
+            .add_tuple(1, [0]);
+        let err = SequenceTarget::new(0, &types).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::WrongShape { .. }));
+
+        // But wrappers up to the maximum depth are looked through:
+        let depth = sequence::MAX_WRAPPER_DEPTH as u32;
+        let types = (1..=depth).fold(
+            MockResolver::new()
+                .add_primitive(0, Primitive::U8)
+                .add_sequence(1, 0),
+            |types, n| types.add_tuple(n + 1, [n]),
+        );
+        assert!(SequenceTarget::new(depth + 1, &types).is_ok());
+        let types = types.add_tuple(depth + 2, [depth + 1]);
+        assert!(SequenceTarget::new(depth + 2, &types).is_err());
+    }
+
+    #[test]
+    fn fields_skipped_in_flattened_fields_cant_be_lined_up_by_position() {
+        #[derive(EncodeAsType)]
+        #[encode_as_type(crate_path = "crate")]
+        struct Foo {
+            a: u8,
+            #[encode_as_type(flatten)]
+            inner: Inner,
+        }
+        #[derive(EncodeAsType)]
+        #[encode_as_type(crate_path = "crate")]
+        struct Inner {
+            #[encode_as_type(skip_if = "Option::is_none")]
+            b: Option<u8>,
+            c: bool,
+        }
+
+        let foo = Foo {
+            a: 1,
+            inner: Inner { b: None, c: true },
+        };
+        let err = encode_type::<_, (u64, bool)>(&foo).unwrap_err();
+        assert!(matches!(
+            err.kind(),
+            ErrorKind::CannotLineUpFieldsByPosition
+        ));
+
+        let foo = Foo {
+            a: 1,
+            inner: Inner {
+                b: Some(2),
+                c: true,
+            },
+        };
+        assert_eq!(
+            encode_type::<_, (u64, Option<u64>, bool)>(&foo).unwrap(),
+            (1u64, Some(2u64), true).encode()
+        );
+    }
 }
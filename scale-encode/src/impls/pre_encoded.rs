@@ -0,0 +1,203 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd. (admin@parity.io)
+// This file is a part of the scale-encode crate.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//         http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+    error::{Error, ErrorKind},
+    EncodeAsType,
+};
+use alloc::{format, string::ToString, vec::Vec};
+use scale_type_resolver::{visitor, Primitive, TypeResolver};
+
+/// Some bytes which are already SCALE encoded, and which will be appended verbatim to the
+/// output when encoded, regardless of the target type.
+///
+/// This is useful in performance critical code where the bytes are already known to line
+/// up with the target type, and so re-encoding them would be wasteful.
+///
+/// # Warning
+///
+/// No validation is performed at all; it is up to the caller to ensure that the bytes are a
+/// valid SCALE encoding of the target type. If they are not, the output will not decode into
+/// the target type. See [`CheckedPreEncoded`] for a variant which performs some basic checks.
+///
+/// ```rust
+/// use codec::Encode;
+/// use scale_encode::{EncodeAsType, PreEncoded};
+/// use scale_info::PortableRegistry;
+///
+/// let m = scale_info::MetaType::new::<(u32, bool)>();
+/// let mut types = scale_info::Registry::new();
+/// let type_id = types.register_type(&m).id;
+/// let types: PortableRegistry = types.into();
+///
+/// let bytes = (123u32, true).encode();
+/// let out = PreEncoded(&bytes).encode_as_type(type_id, &types).unwrap();
+/// assert_eq!(out, bytes);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PreEncoded<'a>(pub &'a [u8]);
+
+impl EncodeAsType for PreEncoded<'_> {
+    fn encode_as_type_to<R: TypeResolver>(
+        &self,
+        _type_id: R::TypeId,
+        _types: &R,
+        out: &mut Vec<u8>,
+    ) -> Result<(), Error> {
+        out.extend_from_slice(self.0);
+        Ok(())
+    }
+}
+
+/// Like [`PreEncoded`], but before appending the bytes to the output, we check that their
+/// length is identical to the encoded size of the target type.
+///
+/// This only works for target types whose encoded size is fixed (ie numbers, bools, chars and
+/// arrays, tuples and composites of these); encoding to any other type will lead to an error.
+/// The bytes themselves are still not validated, and so this does not guarantee that they
+/// will decode into the target type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CheckedPreEncoded<'a>(pub &'a [u8]);
+
+impl EncodeAsType for CheckedPreEncoded<'_> {
+    fn encode_as_type_to<R: TypeResolver>(
+        &self,
+        type_id: R::TypeId,
+        types: &R,
+        out: &mut Vec<u8>,
+    ) -> Result<(), Error> {
+        let Some(expected_len) = fixed_encoded_size(type_id.clone(), types)? else {
+            return Err(Error::custom_string(format!(
+                "Cannot check pre-encoded bytes against type with identifier {type_id:?}, because its encoded size is not fixed or is too large"
+            )));
+        };
+
+        if expected_len != self.0.len() {
+            return Err(Error::new(ErrorKind::WrongLength {
+                actual_len: self.0.len(),
+                expected_len,
+            }));
+        }
+
+        out.extend_from_slice(self.0);
+        Ok(())
+    }
+}
+
+/// A wrapper around some value which implements [`codec::Encode`] but not [`EncodeAsType`],
+/// allowing it to be used where [`EncodeAsType`] is expected. The value is encoded using
+/// [`codec::Encode`], and so this assumes that the `Encode` output matches the target shape.
+///
+/// If the target type has a fixed encoded size (see [`CheckedPreEncoded`]), we check that the
+/// encoded value has the same length and return an error if not. Otherwise, no checks are
+/// performed, and it is up to the caller to ensure that the value lines up with the target type.
+///
+/// ```rust
+/// use codec::Encode;
+/// use scale_encode::{Codec, EncodeAsType};
+/// use scale_info::{PortableRegistry, TypeInfo};
+///
+/// #[derive(Encode, TypeInfo)]
+/// struct Foo {
+///     a: u32,
+///     b: bool,
+/// }
+///
+/// let m = scale_info::MetaType::new::<Foo>();
+/// let mut types = scale_info::Registry::new();
+/// let type_id = types.register_type(&m).id;
+/// let types: PortableRegistry = types.into();
+///
+/// let foo = Foo { a: 123, b: true };
+/// let out = Codec(&foo).encode_as_type(type_id, &types).unwrap();
+/// assert_eq!(out, foo.encode());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Codec<T>(pub T);
+
+impl<T: codec::Encode> EncodeAsType for Codec<T> {
+    fn encode_as_type_to<R: TypeResolver>(
+        &self,
+        type_id: R::TypeId,
+        types: &R,
+        out: &mut Vec<u8>,
+    ) -> Result<(), Error> {
+        let start = out.len();
+        self.0.encode_to(out);
+
+        if let Some(expected_len) = fixed_encoded_size(type_id, types)? {
+            let actual_len = out.len() - start;
+            if actual_len != expected_len {
+                out.truncate(start);
+                return Err(Error::new(ErrorKind::WrongLength {
+                    actual_len,
+                    expected_len,
+                }));
+            }
+        }
+        Ok(())
+    }
+}
+
+// Return the number of bytes that the given type will always encode to, or `None`
+// if the encoded size can vary depending on the value being encoded (or is too large
+// to fit in a usize).
+pub(crate) fn fixed_encoded_size<R: TypeResolver>(
+    type_id: R::TypeId,
+    types: &R,
+) -> Result<Option<usize>, Error> {
+    let v = visitor::new((), |_, _| Ok(None))
+        .visit_not_found(|_| Err(Error::new(ErrorKind::TypeNotFound(format!("{type_id:?}")))))
+        .visit_primitive(|_, primitive| {
+            let size = match primitive {
+                Primitive::Bool | Primitive::U8 | Primitive::I8 => 1,
+                Primitive::U16 | Primitive::I16 => 2,
+                Primitive::Char | Primitive::U32 | Primitive::I32 => 4,
+                Primitive::U64 | Primitive::I64 => 8,
+                Primitive::U128 | Primitive::I128 => 16,
+                Primitive::U256 | Primitive::I256 => 32,
+                Primitive::Str => return Ok(None),
+            };
+            Ok(Some(size))
+        })
+        .visit_array(|_, inner_type_id, len| {
+            Ok(fixed_encoded_size(inner_type_id, types)?.and_then(|size| size.checked_mul(len)))
+        })
+        .visit_tuple(|_, type_ids| {
+            let mut total = Some(0usize);
+            for type_id in type_ids {
+                let Some(size) = fixed_encoded_size(type_id, types)? else {
+                    return Ok(None);
+                };
+                total = total.and_then(|total| total.checked_add(size));
+            }
+            Ok(total)
+        })
+        .visit_composite(|_, _, fields| {
+            let mut total = Some(0usize);
+            for field in fields {
+                let Some(size) = fixed_encoded_size(field.id, types)? else {
+                    return Ok(None);
+                };
+                total = total.and_then(|total| total.checked_add(size));
+            }
+            Ok(total)
+        });
+
+    match types.resolve_type(type_id.clone(), v) {
+        Ok(res) => res,
+        Err(e) => Err(Error::new(ErrorKind::TypeResolvingError(e.to_string()))),
+    }
+}
@@ -0,0 +1,186 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd. (admin@parity.io)
+// This file is a part of the scale-encode crate.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//         http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::pre_encoded::fixed_encoded_size;
+use crate::{
+    error::{collect, Error, ErrorKind, Kind},
+    EncodeAsType,
+};
+use alloc::{format, string::ToString, vec::Vec};
+use codec::{Compact, Encode};
+use scale_type_resolver::{visitor, TypeResolver};
+
+/// A sequence or array type which has been resolved up front, so that many sequences of
+/// values can be encoded into it without resolving it again each time. This is useful when
+/// encoding lots of sequences into the same type, for instance a column of values.
+///
+/// Single field structs and tuples wrapping a sequence or array are resolved to the sequence
+/// or array that they wrap, since they are encoded identically.
+///
+/// ```rust
+/// use codec::Encode;
+/// use scale_encode::SequenceTarget;
+/// use scale_info::PortableRegistry;
+///
+/// let m = scale_info::MetaType::new::<Vec<u64>>();
+/// let mut types = scale_info::Registry::new();
+/// let type_id = types.register_type(&m).id;
+/// let types: PortableRegistry = types.into();
+///
+/// let target = SequenceTarget::new(type_id, &types).unwrap();
+/// for values in [vec![1u8, 2, 3], vec![4, 5]] {
+///     let bytes = target.encode_iter(&values, &types).unwrap();
+///     assert_eq!(bytes, values.iter().map(|&v| v as u64).collect::<Vec<_>>().encode());
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SequenceTarget<TypeId> {
+    item_type_id: TypeId,
+    array_len: Option<usize>,
+    item_size: Option<usize>,
+}
+
+// The maximum number of single field wrappers that we'll look through to find the sequence
+// or array inside them, so that types which wrap themselves are an error rather than
+// recursing forever.
+pub(super) const MAX_WRAPPER_DEPTH: usize = 32;
+
+impl<TypeId: scale_type_resolver::TypeId + 'static> SequenceTarget<TypeId> {
+    /// Resolve the sequence or array type given by `type_id`. An error is returned if
+    /// it is not a sequence or array type (or a single field wrapper around one).
+    pub fn new<R: TypeResolver<TypeId = TypeId>>(
+        type_id: TypeId,
+        types: &R,
+    ) -> Result<Self, Error> {
+        // Look inside single field wrappers for the sequence.
+        let mut inner_type_id = type_id.clone();
+        for _ in 0..=MAX_WRAPPER_DEPTH {
+            match Self::resolve(inner_type_id, types)? {
+                Resolved::Items(item_type_id, array_len) => {
+                    let item_size = fixed_encoded_size(item_type_id.clone(), types)
+                        .ok()
+                        .flatten();
+                    return Ok(SequenceTarget {
+                        item_type_id,
+                        array_len,
+                        item_size,
+                    });
+                }
+                Resolved::Wrapper(next_type_id) => inner_type_id = next_type_id,
+            }
+        }
+        Err(wrong_shape_err(type_id))
+    }
+
+    // Resolve a type to the items of the sequence or array that it is, or to the
+    // type that it wraps if it's a single field wrapper.
+    fn resolve<R: TypeResolver<TypeId = TypeId>>(
+        type_id: TypeId,
+        types: &R,
+    ) -> Result<Resolved<TypeId>, Error> {
+        let v = visitor::new(type_id.clone(), |type_id, _| Err(wrong_shape_err(type_id)))
+            .visit_array(|_, item_type_id, len| Ok(Resolved::Items(item_type_id, Some(len))))
+            .visit_sequence(|_, _, item_type_id| Ok(Resolved::Items(item_type_id, None)))
+            .visit_tuple(
+                |type_id, type_ids| match (type_ids.next(), type_ids.len()) {
+                    (Some(inner_type_id), 0) => Ok(Resolved::Wrapper(inner_type_id)),
+                    _ => Err(wrong_shape_err(type_id)),
+                },
+            )
+            .visit_composite(|type_id, _, fields| match (fields.next(), fields.len()) {
+                (Some(field), 0) => Ok(Resolved::Wrapper(field.id)),
+                _ => Err(wrong_shape_err(type_id)),
+            });
+
+        types
+            .resolve_type(type_id, v)
+            .map_err(|e| Error::new(ErrorKind::TypeResolvingError(e.to_string())))?
+    }
+
+    /// The type ID of the items in this sequence or array.
+    pub fn item_type_id(&self) -> &TypeId {
+        &self.item_type_id
+    }
+
+    /// The length of the array, or `None` if this is a sequence (which can have any length).
+    pub fn array_len(&self) -> Option<usize> {
+        self.array_len
+    }
+
+    /// Encode the values from some iterator into this sequence or array type, writing the
+    /// output bytes to the `out` buffer. `types` must be the same resolver that was used to
+    /// construct this.
+    pub fn encode_iter_to<I, R>(&self, values: I, types: &R, out: &mut Vec<u8>) -> Result<(), Error>
+    where
+        I: IntoIterator,
+        I::IntoIter: ExactSizeIterator,
+        I::Item: EncodeAsType,
+        R: TypeResolver<TypeId = TypeId>,
+    {
+        let values = values.into_iter();
+        let len = values.len();
+
+        match self.array_len {
+            Some(array_len) if array_len != len => {
+                return Err(Error::new(ErrorKind::WrongLength {
+                    actual_len: len,
+                    expected_len: array_len,
+                }))
+            }
+            Some(_) => {}
+            // Sequences are prefixed with their compact encoded length:
+            None => Compact(len as u32).encode_to(out),
+        }
+
+        let reserve = len.saturating_mul(self.item_size.unwrap_or(1));
+        out.reserve(reserve.min(super::MAX_RESERVE_BYTES));
+        for (idx, value) in values.enumerate() {
+            collect::nested(
+                || value.encode_as_type_to(self.item_type_id.clone(), types, out),
+                |e| e.at_idx(idx),
+            )?;
+        }
+        Ok(())
+    }
+
+    /// This is similar to [`SequenceTarget::encode_iter_to`], except that it
+    /// allocates a new [`Vec`] to write the output bytes to and returns it.
+    pub fn encode_iter<I, R>(&self, values: I, types: &R) -> Result<Vec<u8>, Error>
+    where
+        I: IntoIterator,
+        I::IntoIter: ExactSizeIterator,
+        I::Item: EncodeAsType,
+        R: TypeResolver<TypeId = TypeId>,
+    {
+        let mut out = Vec::new();
+        self.encode_iter_to(values, types, &mut out)?;
+        Ok(out)
+    }
+}
+
+fn wrong_shape_err(type_id: impl core::fmt::Debug) -> Error {
+    Error::new(ErrorKind::WrongShape {
+        actual: Kind::Array,
+        expected_id: format!("{type_id:?}"),
+    })
+}
+
+// The result of resolving the type given to a SequenceTarget.
+enum Resolved<TypeId> {
+    // A sequence or array, and the array length if it's an array.
+    Items(TypeId, Option<usize>),
+    // A single field wrapper around the given type.
+    Wrapper(TypeId),
+}
@@ -0,0 +1,90 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd. (admin@parity.io)
+// This file is a part of the scale-encode crate.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//         http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{error::Error, EncodeAsType};
+use alloc::vec::Vec;
+use scale_type_resolver::TypeResolver;
+use std::sync::{Mutex, RwLock};
+
+/// A [`Mutex`] is locked for the duration of encoding, and the value inside is encoded.
+/// This means that `Arc<Mutex<T>>` can be encoded too, since `Arc<T>` encodes like `T`.
+///
+/// If the lock is poisoned, the value may be in an inconsistent state, and so an error is
+/// returned rather than encoding it.
+impl<T: EncodeAsType + ?Sized> EncodeAsType for Mutex<T> {
+    fn encode_as_type_to<R: TypeResolver>(
+        &self,
+        type_id: R::TypeId,
+        types: &R,
+        out: &mut Vec<u8>,
+    ) -> Result<(), Error> {
+        let val = self
+            .lock()
+            .map_err(|_| Error::custom_str("Cannot encode the value in a poisoned Mutex"))?;
+        val.encode_as_type_to(type_id, types, out)
+    }
+}
+
+/// Like [`Mutex`], a read lock is taken on an [`RwLock`] to encode the value inside it, and
+/// an error is returned if the lock is poisoned.
+impl<T: EncodeAsType + ?Sized> EncodeAsType for RwLock<T> {
+    fn encode_as_type_to<R: TypeResolver>(
+        &self,
+        type_id: R::TypeId,
+        types: &R,
+        out: &mut Vec<u8>,
+    ) -> Result<(), Error> {
+        let val = self
+            .read()
+            .map_err(|_| Error::custom_str("Cannot encode the value in a poisoned RwLock"))?;
+        val.encode_as_type_to(type_id, types, out)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::encode_type;
+    use alloc::{sync::Arc, vec};
+    use codec::Encode;
+
+    #[test]
+    fn locks_encode_their_values() {
+        let value = Arc::new(Mutex::new(42u64));
+        assert_eq!(encode_type::<_, u64>(&value).unwrap(), 42u64.encode());
+        // The lock is released again after encoding:
+        assert!(value.try_lock().is_ok());
+
+        let value = Arc::new(RwLock::new(vec![1u8, 2, 3]));
+        assert_eq!(
+            encode_type::<_, Vec<u64>>(&value).unwrap(),
+            vec![1u64, 2, 3].encode()
+        );
+    }
+
+    #[test]
+    fn poisoned_locks_cannot_be_encoded() {
+        let value = Arc::new(Mutex::new(42u64));
+        let value2 = value.clone();
+        let _ = std::thread::spawn(move || {
+            let _guard = value2.lock().unwrap();
+            panic!("poison the lock");
+        })
+        .join();
+
+        assert!(value.is_poisoned());
+        assert!(encode_type::<_, u64>(&value).is_err());
+    }
+}
@@ -14,7 +14,10 @@
 // limitations under the License.
 
 use super::composite::{Composite, CompositeField};
-use crate::error::{Error, ErrorKind, Kind};
+use crate::{
+    error::{collect, Error, ErrorKind, Kind},
+    EncodeAsType,
+};
 use alloc::{format, string::ToString, vec::Vec};
 use codec::Encode;
 use scale_type_resolver::{visitor, TypeResolver};
@@ -84,6 +87,154 @@ where
         type_id: R::TypeId,
         types: &R,
         out: &mut Vec<u8>,
+    ) -> Result<(), Error> {
+        self.encode_variant_as_type_with_fallback_to(type_id, types, &[], None, out)
+    }
+
+    /// A shortcut for [`Self::encode_variant_as_type_with_alternatives_to()`] which
+    /// internally allocates a [`Vec`] and returns it.
+    pub fn encode_variant_as_type_with_alternatives(
+        &self,
+        alternative_names: &[&str],
+        type_id: R::TypeId,
+        types: &R,
+    ) -> Result<Vec<u8>, Error> {
+        let mut out = Vec::new();
+        self.encode_variant_as_type_with_alternatives_to(
+            alternative_names,
+            type_id,
+            types,
+            &mut out,
+        )?;
+        Ok(out)
+    }
+
+    /// Encode the variant as the provided type to the output bytes. If the target type has no
+    /// variant with our [`Variant::name`], then each of the alternative names given is tried in
+    /// order, and the first one that exists on the target type is used. This is useful when a
+    /// variant may have one of several names, for instance across different runtime versions.
+    ///
+    /// If none of the names exist, an [`ErrorKind::CannotFindVariant`] error is returned whose
+    /// name lists each of the names that were tried.
+    ///
+    /// ```rust
+    /// use codec::Encode;
+    /// use scale_encode::{ Composite, CompositeField, Variant };
+    /// use scale_info::{ PortableRegistry, TypeInfo };
+    ///
+    /// #[derive(Encode, TypeInfo)]
+    /// enum Call {
+    ///     TransferAllowDeath(u64),
+    /// }
+    ///
+    /// let m = scale_info::MetaType::new::<Call>();
+    /// let mut types = scale_info::Registry::new();
+    /// let type_id = types.register_type(&m).id;
+    /// let types: PortableRegistry = types.into();
+    ///
+    /// let variant = Variant {
+    ///     name: "Transfer",
+    ///     fields: Composite::new([(None, CompositeField::new(&123u8))].into_iter()),
+    /// };
+    /// let bytes = variant
+    ///     .encode_variant_as_type_with_alternatives(&["TransferAllowDeath"], type_id, &types)
+    ///     .unwrap();
+    ///
+    /// assert_eq!(bytes, Call::TransferAllowDeath(123).encode());
+    /// ```
+    pub fn encode_variant_as_type_with_alternatives_to(
+        &self,
+        alternative_names: &[&str],
+        type_id: R::TypeId,
+        types: &R,
+        out: &mut Vec<u8>,
+    ) -> Result<(), Error> {
+        self.encode_variant_as_type_with_fallback_to(type_id, types, alternative_names, None, out)
+    }
+
+    /// A shortcut for [`Self::encode_variant_or_discriminant_as_type_to()`] which
+    /// internally allocates a [`Vec`] and returns it.
+    pub fn encode_variant_or_discriminant_as_type(
+        &self,
+        discriminant: i128,
+        type_id: R::TypeId,
+        types: &R,
+    ) -> Result<Vec<u8>, Error> {
+        let mut out = Vec::new();
+        self.encode_variant_or_discriminant_as_type_to(discriminant, type_id, types, &mut out)?;
+        Ok(out)
+    }
+
+    /// Encode the variant as the provided type to the output bytes. If the target type is not
+    /// a variant type, then the given discriminant is encoded into it instead, as a number. An
+    /// [`ErrorKind::NumberOutOfRange`] error is returned if it doesn't fit into the target.
+    /// This is useful for fieldless enums, which may be represented by their discriminant in
+    /// the target type.
+    ///
+    /// Variants which have fields cannot be encoded as a discriminant, since their fields would
+    /// be lost; an error is returned if this is attempted.
+    ///
+    /// ```rust
+    /// use codec::Encode;
+    /// use scale_encode::{ Composite, Variant };
+    /// use scale_info::PortableRegistry;
+    ///
+    /// let m = scale_info::MetaType::new::<u8>();
+    /// let mut types = scale_info::Registry::new();
+    /// let type_id = types.register_type(&m).id;
+    /// let types: PortableRegistry = types.into();
+    ///
+    /// let variant = Variant {
+    ///     name: "Foo",
+    ///     fields: Composite::new([].into_iter()),
+    /// };
+    /// let bytes = variant.encode_variant_or_discriminant_as_type(2, type_id, &types).unwrap();
+    ///
+    /// assert_eq!(bytes, 2u8.encode());
+    /// ```
+    pub fn encode_variant_or_discriminant_as_type_to(
+        &self,
+        discriminant: i128,
+        type_id: R::TypeId,
+        types: &R,
+        out: &mut Vec<u8>,
+    ) -> Result<(), Error> {
+        let inner_type_id = super::find_single_entry_with_same_repr(type_id.clone(), types);
+        let v = visitor::new((), |_, _| false).visit_variant(|_, _, _| true);
+        let is_variant = types.resolve_type(inner_type_id, v).unwrap_or(true);
+
+        if is_variant {
+            return self.encode_variant_as_type_to(type_id, types, out);
+        }
+        if self.fields.has_values() {
+            return Err(Error::custom_string(format!(
+                "Cannot encode variant {} as a discriminant, because it has fields",
+                self.name
+            )));
+        }
+
+        trace_decision!(
+            "encode variant {} as discriminant {discriminant}",
+            self.name
+        );
+        collect::nested(
+            || discriminant.encode_as_type_to(type_id, types, out),
+            |e| e.at_variant(self.name.to_string()),
+        )
+    }
+
+    // Encode the variant as the provided type to the output bytes. We prefer a variant with
+    // our name, and then variants with each of the alternative names in order. If no variant
+    // has a matching name, then use the variant with the given fallback index if one is
+    // provided. This is useful for generic types like `Either` whose variant names are
+    // unlikely to line up with those on the target type.
+    pub(crate) fn encode_variant_as_type_with_fallback_to(
+        &self,
+        type_id: R::TypeId,
+        types: &R,
+        alternative_names: &[&str],
+        fallback_index: Option<u8>,
+        out: &mut Vec<u8>,
     ) -> Result<(), Error> {
         let type_id = super::find_single_entry_with_same_repr(type_id, types);
 
@@ -94,24 +245,55 @@ where
             }))
         })
         .visit_variant(|type_id, _, vars| {
+            let names = || core::iter::once(self.name).chain(alternative_names.iter().copied());
+
+            // Find the variant whose name appears earliest in our list of names.
             let mut res = None;
+            let mut res_pos = usize::MAX;
+            let mut fallback = None;
             for var in vars {
-                if var.name == self.name {
-                    res = Some(var);
-                    break;
+                if let Some(pos) = names().position(|name| name == var.name) {
+                    if pos < res_pos {
+                        res = Some(var);
+                        res_pos = pos;
+                        if pos == 0 {
+                            break;
+                        }
+                    }
+                    continue;
+                }
+                if fallback.is_none() && Some(var.index) == fallback_index {
+                    fallback = Some(var);
                 }
             }
 
+            if res.is_none() && fallback.is_some() {
+                trace_decision!(
+                    "no variant named {}; falling back to variant with index {}",
+                    self.name,
+                    fallback_index.unwrap_or_default()
+                );
+                res = fallback;
+            }
+
             let Some(mut var) = res else {
                 return Err(Error::new(ErrorKind::CannotFindVariant {
-                    name: self.name.to_string(),
+                    name: names().collect::<Vec<_>>().join(" or "),
                     expected_id: format!("{type_id:?}"),
                 }));
             };
 
+            trace_decision!("encode variant {} with index {}", var.name, var.index);
             var.index.encode_to(out);
-            self.fields
-                .encode_composite_fields_to(&mut var.fields, types, out)
+            // Point errors at the target variant that we chose, which may not have our name.
+            let var_name = var.name;
+            collect::nested(
+                || {
+                    self.fields
+                        .encode_composite_fields_to(&mut var.fields, types, out)
+                },
+                |e| e.at_variant(var_name.to_string()),
+            )
         });
 
         super::resolve_type_and_encode(types, type_id, v)
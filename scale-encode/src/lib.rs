@@ -69,6 +69,11 @@ where
 assert_encodes_to(123u8, 123u64);
 assert_encodes_to(123u64, 123u8);
 
+// `usize` and `isize` are also converted into the desired output size, so the width of
+// the platform doing the encoding never affects the encoded bytes:
+assert_encodes_to(123usize, 123u32);
+assert_encodes_to(-123isize, -123i64);
+
 // Compact encoding is also handled "under the hood" by EncodeAsType, so no "compact"
 // annotations are needed on values.
 assert_encodes_to(123u64, codec::Compact(123u64));
@@ -142,19 +147,45 @@ assert_encodes_to(
 #![deny(missing_docs)]
 
 extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
+// Record a decision taken while encoding if the "trace" feature is enabled.
+// This compiles to nothing otherwise.
+macro_rules! trace_decision {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "trace")]
+        $crate::trace::record(|| $crate::trace::TraceEvent::Decision(alloc::format!($($arg)*)));
+    };
+}
 
 mod impls;
 
 pub mod error;
+pub mod resolver;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+#[cfg(feature = "trace")]
+pub mod trace;
+pub mod util;
 
 // This is exported for generated derive code to use, to be compatible with std or no-std as needed.
 #[doc(hidden)]
 pub use alloc::vec::Vec;
+// This is exported for generated derive code to use when encoding `#[encode_as_type(with = "..")]` fields.
+#[doc(hidden)]
+pub use crate::impls::EncodeFieldWith;
 
 pub use error::Error;
 
 // Useful types to help implement EncodeAsType/Fields with:
-pub use crate::impls::{Composite, CompositeField, Variant};
+pub use crate::impls::{
+    AsBytes, ByKeyOrder, CheckedPreEncoded, Codec, Composite, CompositeField, FlattenFields, Hex,
+    NumberAsBool, OkOnly, OptionAsSeq, PreEncoded, Seq, SequenceTarget, Tagged, Variant,
+    WithContext,
+};
+#[cfg(feature = "base64")]
+pub use crate::impls::{Base64, Base64Alphabet};
 pub use scale_type_resolver::{Field, FieldIter, TypeResolver};
 
 /// Re-exports of external crates.
@@ -163,6 +194,10 @@ pub mod ext {
     pub use primitive_types;
 }
 
+// The primitive type that some numbers are, and a function to write a slice of them to
+// some output. See `EncodeAsType::__scalar_encoding`.
+type ScalarEncoding<T> = (scale_type_resolver::Primitive, fn(&[T], &mut Vec<u8>));
+
 /// This trait signals that some static type can possibly be SCALE encoded given some
 /// `type_id` and a corresponding [`TypeResolver`] which tells us about the expected encoding.
 pub trait EncodeAsType {
@@ -186,6 +221,59 @@ pub trait EncodeAsType {
         self.encode_as_type_to(type_id, types, &mut out)?;
         Ok(out)
     }
+
+    // This is an implementation detail which allows slices of numbers (notably bytes) to be
+    // encoded more efficiently when the target is a sequence or array of the same primitive
+    // type. If given, the function must write the slice as it would be SCALE encoded into
+    // a sequence or array of that primitive, without any length prefix. It should not be
+    // overridden.
+    #[doc(hidden)]
+    fn __scalar_encoding() -> Option<ScalarEncoding<Self>>
+    where
+        Self: Sized,
+    {
+        None
+    }
+
+    /// Like [`EncodeAsType::encode_as_type`], but also returns a trace of each type that was
+    /// resolved and each decision taken while encoding the value. This is useful to help debug
+    /// why some value failed to encode or was encoded in an unexpected way.
+    #[cfg(feature = "trace")]
+    fn encode_as_type_traced<R: TypeResolver>(
+        &self,
+        type_id: R::TypeId,
+        types: &R,
+    ) -> (Result<Vec<u8>, Error>, Vec<trace::TraceEvent>) {
+        trace::collect(|| self.encode_as_type(type_id, types))
+    }
+
+    /// Like [`EncodeAsType::encode_as_type`], but rather than stopping at the first error, this
+    /// carries on encoding any other fields, variants and sequence items after one fails to
+    /// encode, and returns every error that was encountered. This is useful for validating that
+    /// some value can be encoded into a type, and reporting all of the problems at once.
+    ///
+    /// The bytes for anything which fails to encode are missing, and so no bytes are returned
+    /// if there are any errors.
+    ///
+    /// While errors are being collected, nested values which fail to encode record their error
+    /// and then return `Ok(())` so that encoding can carry on. This means that any impl which
+    /// tries to encode something and falls back to something else if that returns an error would
+    /// take the wrong branch. Such impls should make their first attempt inside
+    /// [`error::suspend_error_collection()`], which ensures that any errors are returned as usual.
+    #[cfg(feature = "std")]
+    fn encode_as_type_collect_errors<R: TypeResolver>(
+        &self,
+        type_id: R::TypeId,
+        types: &R,
+    ) -> Result<Vec<u8>, Vec<Error>> {
+        let (res, mut errors) = error::collect::collect(|| self.encode_as_type(type_id, types));
+        match res {
+            Ok(bytes) if errors.is_empty() => return Ok(bytes),
+            Ok(_) => {}
+            Err(e) => errors.push(e),
+        }
+        Err(errors)
+    }
 }
 
 /// This is similar to [`EncodeAsType`], except that it can be implemented on types that can be encoded
@@ -213,6 +301,110 @@ pub trait EncodeAsFields {
     }
 }
 
+/// [`EncodeAsType`] is not object safe, because its methods are generic over the
+/// [`TypeResolver`] used. This trait fixes the [`TypeResolver`] up front and is object safe,
+/// which allows things like `&[&dyn DynEncodeAsType<R>]` to be constructed. It is
+/// automatically implemented for any type which implements [`EncodeAsType`].
+///
+/// Slices of such trait objects themselves implement [`DynEncodeAsType`] and [`DynEncodeAsFields`],
+/// and are encoded much like tuples, except that each value can be of a different type.
+///
+/// ```rust
+/// use codec::Encode;
+/// use scale_encode::DynEncodeAsType;
+/// use scale_info::{PortableRegistry, TypeInfo};
+///
+/// #[derive(Encode, TypeInfo)]
+/// struct Foo {
+///     a: u64,
+///     b: bool,
+///     c: String,
+/// }
+///
+/// let values: [&dyn DynEncodeAsType<PortableRegistry>; 3] = [&1u8, &true, &"hello"];
+///
+/// # let m = scale_info::MetaType::new::<Foo>();
+/// # let mut types = scale_info::Registry::new();
+/// # let type_id = types.register_type(&m).id;
+/// # let types: PortableRegistry = types.into();
+/// let mut out = Vec::new();
+/// values[..].encode_as_type_with_resolver_to(type_id, &types, &mut out).unwrap();
+///
+/// assert_eq!(out, Foo { a: 1, b: true, c: "hello".to_string() }.encode());
+/// ```
+pub trait DynEncodeAsType<R: TypeResolver> {
+    /// Given some `type_id`, `types` and some output target for the SCALE encoded bytes,
+    /// attempt to SCALE encode the current value into the type given by `type_id`.
+    fn encode_as_type_with_resolver_to(
+        &self,
+        type_id: R::TypeId,
+        types: &R,
+        out: &mut Vec<u8>,
+    ) -> Result<(), Error>;
+}
+
+impl<T: EncodeAsType, R: TypeResolver> DynEncodeAsType<R> for T {
+    fn encode_as_type_with_resolver_to(
+        &self,
+        type_id: R::TypeId,
+        types: &R,
+        out: &mut Vec<u8>,
+    ) -> Result<(), Error> {
+        self.encode_as_type_to(type_id, types, out)
+    }
+}
+
+/// [`EncodeAsFields`] is not object safe, because its methods are generic over the
+/// [`TypeResolver`] used. This trait fixes the [`TypeResolver`] up front and is object safe,
+/// which allows things like `Vec<Box<dyn DynEncodeAsFields<R>>>` to be constructed. It is
+/// automatically implemented for any type which implements [`EncodeAsFields`].
+///
+/// ```rust
+/// use scale_encode::{DynEncodeAsFields, EncodeAsType, Field};
+/// use scale_info::PortableRegistry;
+/// use std::collections::BTreeMap;
+///
+/// #[derive(EncodeAsType)]
+/// struct Foo {
+///     a: u64,
+/// }
+///
+/// let row: Vec<Box<dyn DynEncodeAsFields<PortableRegistry>>> = vec![
+///     Box::new(Foo { a: 1 }),
+///     Box::new(BTreeMap::from([("a", 2u8)])),
+/// ];
+///
+/// # let m = scale_info::MetaType::new::<u64>();
+/// # let mut types = scale_info::Registry::new();
+/// # let type_id = types.register_type(&m).id;
+/// # let types: PortableRegistry = types.into();
+/// for value in &row {
+///     let mut fields = [Field::named(type_id, "a")].into_iter();
+///     let mut out = Vec::new();
+///     value.encode_as_fields_with_resolver_to(&mut fields, &types, &mut out).unwrap();
+/// }
+/// ```
+pub trait DynEncodeAsFields<R: TypeResolver> {
+    /// Given some fields describing the shape of a type, attempt to encode to that shape.
+    fn encode_as_fields_with_resolver_to(
+        &self,
+        fields: &mut dyn FieldIter<'_, R::TypeId>,
+        types: &R,
+        out: &mut Vec<u8>,
+    ) -> Result<(), Error>;
+}
+
+impl<T: EncodeAsFields + ?Sized, R: TypeResolver> DynEncodeAsFields<R> for T {
+    fn encode_as_fields_with_resolver_to(
+        &self,
+        fields: &mut dyn FieldIter<'_, R::TypeId>,
+        types: &R,
+        out: &mut Vec<u8>,
+    ) -> Result<(), Error> {
+        self.encode_as_fields_to(fields, types, out)
+    }
+}
+
 /// The `EncodeAsType` derive macro can be used to implement `EncodeAsType`
 /// on structs and enums whose fields all implement `EncodeAsType`.
 ///
@@ -266,7 +458,8 @@ pub trait EncodeAsFields {
 /// struct Foo<T> {
 ///    a: u64,
 ///    b: bool,
-///    c: std::marker::PhantomData<T>
+///    #[encode_as_type(skip)]
+///    c: T
 /// }
 /// ```
 ///
@@ -280,5 +473,56 @@ pub trait EncodeAsFields {
 ///   By default, for each generate type parameter, the macro will add trait bounds such
 ///   that these type parameters must implement `EncodeAsType` too. You can override this
 ///   behaviour and provide your own trait bounds instead using this option.
+/// - `#[encode_as_type(bound = "T: Foo")]`:
+///   On a field, this adds the given trait bounds (which may be empty, ie `bound = ""`) instead of
+///   the default `EncodeAsType` bounds for the type parameters used in that field. Type parameters
+///   which are also used in fields without a `bound` still get the default bounds. This is useful
+///   for fields like skipped fields, which don't need `T: EncodeAsType`.
+/// - `#[encode_as_type(discriminant)]`:
+///   On an enum whose variants have no fields, this allows each variant to be encoded as its
+///   discriminant (eg `2` for `Foo = 2`) when the target type is a number rather than a variant
+///   type. Variant targets are still encoded by name. See
+///   [`Variant::encode_variant_or_discriminant_as_type_to()`].
+/// - `#[encode_as_type(no_fields)]`:
+///   On a struct, this stops the macro from also implementing [`EncodeAsFields`], so that
+///   you can implement it yourself instead.
+/// - `#[encode_as_type(skip)]` (or `#[codec(skip)]`):
+///   On a field, this ignores the field entirely when encoding. On an enum variant, this
+///   marks the variant as not encodable, and an error will be returned if we try to encode it.
+/// - `#[encode_as_type(skip_if = "Option::is_none")]`:
+///   On a named field, this calls the given function with a reference to the field, and leaves
+///   the field out when encoding if it returns `true`. Since this changes the number of fields
+///   being encoded, it only works when fields are lined up with the target type by name; if a
+///   field is skipped and the target fields are unnamed, an
+///   [`error::ErrorKind::CannotLineUpFieldsByPosition`] error is returned rather than lining up the
+///   remaining values by position. This can't be used on unnamed fields.
+/// - `#[encode_as_type(flatten)]`:
+///   On a field, this splices the fields of the field's value into the parent, rather than
+///   encoding it as a single nested value. The field's type must implement [`FlattenFields`],
+///   which is the case for tuples and for structs which derive `EncodeAsType` (unless they
+///   contain fields encoded `with` some function).
+/// - `#[encode_as_type(with = "path::to::encode")]`:
+///   On a field, this encodes the field using the given function instead of its `EncodeAsType`
+///   impl, so the field's type doesn't need to implement `EncodeAsType`. The function should
+///   look like `fn encode<R: TypeResolver>(value: &FieldTy, type_id: R::TypeId, types: &R, out:
+///   &mut Vec<u8>) -> Result<(), Error>`. Structs containing such fields don't implement
+///   [`FlattenFields`], and so can't themselves be flattened.
+/// - `#[encode_as_type(rename = "name")]`:
+///   On a named field or an enum variant, this encodes it as if it were called `name`, so that
+///   it lines up with a target field or variant of that name. This is useful when the target
+///   name is a Rust keyword or doesn't follow Rust naming conventions.
+/// - `#[encode_as_type(ignore_trailing_fields)]`:
+///   On an enum variant with unnamed fields, this allows it to be encoded into a target variant
+///   with fewer fields, ignoring any trailing fields that the target has no room for (named
+///   fields which the target doesn't have are always ignored). See
+///   [`Composite::ignore_trailing_fields()`].
+///
+/// Fields whose type is `PhantomData<..>` are skipped unless they are marked with
+/// `#[encode_as_type(skip = false)]`, in which case they are encoded like `()`. Either way, type
+/// parameters used only in such fields don't get the default `EncodeAsType` bounds.
+///
+/// Structs marked `#[repr(transparent)]` which have exactly one field that isn't skipped are
+/// first encoded as a composite type like any other struct, and if that fails, they are encoded
+/// exactly as that field would be.
 #[cfg(feature = "derive")]
 pub use scale_encode_derive::EncodeAsType;
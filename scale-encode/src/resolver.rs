@@ -0,0 +1,345 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd. (admin@parity.io)
+// This file is a part of the scale-encode crate.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//         http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! This module provides [`TypeResolver`] adapters, which wrap some other
+//! [`TypeResolver`] in order to change or extend its behaviour.
+
+#[cfg(feature = "trace")]
+use alloc::vec::Vec;
+use core::cell::Cell;
+#[cfg(feature = "trace")]
+use core::cell::RefCell;
+#[cfg(feature = "trace")]
+use scale_type_resolver::{
+    BitsOrderFormat, BitsStoreFormat, FieldIter, PathIter, Primitive, UnhandledKind, VariantIter,
+};
+use scale_type_resolver::{ResolvedTypeVisitor, TypeResolver};
+
+/// A [`TypeResolver`] which wraps another, and returns an error if types are resolved
+/// while more than `max_depth` other types are already being resolved.
+///
+/// Encoding a value is recursive, and encoding deeply nested values (for instance a long
+/// cons list, or values whose type is cyclic) may otherwise overflow the stack. Using this
+/// adapter converts such cases into an error instead.
+///
+/// ```rust
+/// use scale_encode::{resolver::DepthLimited, EncodeAsType};
+/// use scale_info::PortableRegistry;
+///
+/// let m = scale_info::MetaType::new::<Vec<Vec<u8>>>();
+/// let mut types = scale_info::Registry::new();
+/// let type_id = types.register_type(&m).id;
+/// let types: PortableRegistry = types.into();
+///
+/// let value = vec![vec![1u8, 2, 3]];
+///
+/// let limited = DepthLimited::new(&types, 3);
+/// assert!(value.encode_as_type(type_id, &limited).is_ok());
+///
+/// let limited = DepthLimited::new(&types, 2);
+/// assert!(value.encode_as_type(type_id, &limited).is_err());
+/// ```
+#[derive(Debug)]
+pub struct DepthLimited<'r, R> {
+    inner: &'r R,
+    max_depth: usize,
+    depth: Cell<usize>,
+}
+
+impl<'r, R: TypeResolver> DepthLimited<'r, R> {
+    /// Wrap a [`TypeResolver`], allowing at most `max_depth` nested type resolutions.
+    pub fn new(inner: &'r R, max_depth: usize) -> Self {
+        DepthLimited {
+            inner,
+            max_depth,
+            depth: Cell::new(0),
+        }
+    }
+}
+
+/// An error returned from [`DepthLimited`].
+#[derive(Debug, thiserror::Error)]
+pub enum DepthLimitedError<E> {
+    /// Type resolution was nested too deeply.
+    #[error("Maximum type resolution depth of {0} exceeded")]
+    DepthExceeded(usize),
+    /// The wrapped [`TypeResolver`] returned an error.
+    #[error("{0}")]
+    Inner(E),
+}
+
+impl<R: TypeResolver> TypeResolver for DepthLimited<'_, R> {
+    type TypeId = R::TypeId;
+    type Error = DepthLimitedError<R::Error>;
+
+    fn resolve_type<'this, V: ResolvedTypeVisitor<'this, TypeId = Self::TypeId>>(
+        &'this self,
+        type_id: Self::TypeId,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        let depth = self.depth.get();
+        if depth >= self.max_depth {
+            return Err(DepthLimitedError::DepthExceeded(self.max_depth));
+        }
+
+        self.depth.set(depth + 1);
+        let res = self
+            .inner
+            .resolve_type(type_id, visitor)
+            .map_err(DepthLimitedError::Inner);
+        self.depth.set(depth);
+        res
+    }
+}
+
+/// A [`TypeResolver`] which wraps another, and records each type that is resolved along
+/// with the shape that it resolved to. This can help to diagnose why some value was encoded
+/// in an unexpected way, or failed to encode. This is only available with the `trace` feature.
+///
+/// ```rust
+/// use scale_encode::{resolver::{LoggingResolver, ResolvedKind}, EncodeAsType};
+/// use scale_info::PortableRegistry;
+///
+/// let m = scale_info::MetaType::new::<(u8, bool)>();
+/// let mut types = scale_info::Registry::new();
+/// let type_id = types.register_type(&m).id;
+/// let types: PortableRegistry = types.into();
+///
+/// let logging = LoggingResolver::new(&types);
+/// (1u64, true).encode_as_type(type_id, &logging).unwrap();
+///
+/// let kinds: Vec<_> = logging.take_resolutions().into_iter().map(|r| r.kind).collect();
+/// assert_eq!(kinds[0], ResolvedKind::Tuple);
+/// assert!(kinds.contains(&ResolvedKind::Primitive(scale_type_resolver::Primitive::U8)));
+/// assert!(kinds.contains(&ResolvedKind::Primitive(scale_type_resolver::Primitive::Bool)));
+/// ```
+#[cfg(feature = "trace")]
+#[derive(Debug)]
+pub struct LoggingResolver<'r, R: TypeResolver> {
+    inner: &'r R,
+    resolutions: RefCell<Vec<Resolution<R::TypeId>>>,
+}
+
+#[cfg(feature = "trace")]
+impl<'r, R: TypeResolver> LoggingResolver<'r, R> {
+    /// Wrap a [`TypeResolver`], recording each type that it resolves.
+    pub fn new(inner: &'r R) -> Self {
+        LoggingResolver {
+            inner,
+            resolutions: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// Return the types that have been resolved so far, in the order that they were
+    /// resolved, and clear the record of them.
+    pub fn take_resolutions(&self) -> Vec<Resolution<R::TypeId>> {
+        core::mem::take(&mut *self.resolutions.borrow_mut())
+    }
+}
+
+#[cfg(feature = "trace")]
+/// A type which was resolved by a [`LoggingResolver`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Resolution<TypeId> {
+    /// The identifier of the type that was resolved.
+    pub type_id: TypeId,
+    /// The shape of the type that was resolved.
+    pub kind: ResolvedKind,
+}
+
+#[cfg(feature = "trace")]
+/// The shape that a type resolved to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResolvedKind {
+    /// The type was not found.
+    NotFound,
+    /// The type could not be handled by the resolver.
+    Unhandled(UnhandledKind),
+    /// A composite type.
+    Composite,
+    /// A variant type.
+    Variant,
+    /// A sequence type.
+    Sequence,
+    /// An array type.
+    Array,
+    /// A tuple type.
+    Tuple,
+    /// A primitive type.
+    Primitive(Primitive),
+    /// A compact encoded type.
+    Compact,
+    /// A bit sequence type.
+    BitSequence,
+}
+
+#[cfg(feature = "trace")]
+impl<R: TypeResolver> TypeResolver for LoggingResolver<'_, R> {
+    type TypeId = R::TypeId;
+    type Error = R::Error;
+
+    fn resolve_type<'this, V: ResolvedTypeVisitor<'this, TypeId = Self::TypeId>>(
+        &'this self,
+        type_id: Self::TypeId,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        let visitor = LoggingVisitor {
+            type_id: type_id.clone(),
+            resolutions: &self.resolutions,
+            inner: visitor,
+        };
+        self.inner.resolve_type(type_id, visitor)
+    }
+}
+
+// A visitor which records the shape that a type resolves to before
+// handing over to the visitor that it wraps.
+#[cfg(feature = "trace")]
+struct LoggingVisitor<'a, TypeId, V> {
+    type_id: TypeId,
+    resolutions: &'a RefCell<Vec<Resolution<TypeId>>>,
+    inner: V,
+}
+
+#[cfg(feature = "trace")]
+impl<TypeId, V> LoggingVisitor<'_, TypeId, V> {
+    fn record(&self, kind: ResolvedKind)
+    where
+        TypeId: Clone,
+    {
+        self.resolutions.borrow_mut().push(Resolution {
+            type_id: self.type_id.clone(),
+            kind,
+        });
+    }
+}
+
+#[cfg(feature = "trace")]
+impl<'resolver, V: ResolvedTypeVisitor<'resolver>> ResolvedTypeVisitor<'resolver>
+    for LoggingVisitor<'_, V::TypeId, V>
+{
+    type TypeId = V::TypeId;
+    type Value = V::Value;
+
+    fn visit_unhandled(self, kind: UnhandledKind) -> Self::Value {
+        self.record(ResolvedKind::Unhandled(kind));
+        self.inner.visit_unhandled(kind)
+    }
+    fn visit_not_found(self) -> Self::Value {
+        self.record(ResolvedKind::NotFound);
+        self.inner.visit_not_found()
+    }
+    fn visit_composite<Path, Fields>(self, path: Path, fields: Fields) -> Self::Value
+    where
+        Path: PathIter<'resolver>,
+        Fields: FieldIter<'resolver, Self::TypeId>,
+    {
+        self.record(ResolvedKind::Composite);
+        self.inner.visit_composite(path, fields)
+    }
+    fn visit_variant<Path, Fields, Var>(self, path: Path, variants: Var) -> Self::Value
+    where
+        Path: PathIter<'resolver>,
+        Fields: FieldIter<'resolver, Self::TypeId>,
+        Var: VariantIter<'resolver, Fields>,
+    {
+        self.record(ResolvedKind::Variant);
+        self.inner.visit_variant(path, variants)
+    }
+    fn visit_sequence<Path>(self, path: Path, type_id: Self::TypeId) -> Self::Value
+    where
+        Path: PathIter<'resolver>,
+    {
+        self.record(ResolvedKind::Sequence);
+        self.inner.visit_sequence(path, type_id)
+    }
+    fn visit_array(self, type_id: Self::TypeId, len: usize) -> Self::Value {
+        self.record(ResolvedKind::Array);
+        self.inner.visit_array(type_id, len)
+    }
+    fn visit_tuple<TypeIds>(self, type_ids: TypeIds) -> Self::Value
+    where
+        TypeIds: ExactSizeIterator<Item = Self::TypeId>,
+    {
+        self.record(ResolvedKind::Tuple);
+        self.inner.visit_tuple(type_ids)
+    }
+    fn visit_primitive(self, primitive: Primitive) -> Self::Value {
+        self.record(ResolvedKind::Primitive(primitive));
+        self.inner.visit_primitive(primitive)
+    }
+    fn visit_compact(self, type_id: Self::TypeId) -> Self::Value {
+        self.record(ResolvedKind::Compact);
+        self.inner.visit_compact(type_id)
+    }
+    fn visit_bit_sequence(
+        self,
+        store_format: BitsStoreFormat,
+        order_format: BitsOrderFormat,
+    ) -> Self::Value {
+        self.record(ResolvedKind::BitSequence);
+        self.inner.visit_bit_sequence(store_format, order_format)
+    }
+}
+
+#[cfg(all(test, feature = "trace"))]
+mod test {
+    use super::*;
+    use crate::testing::make_type;
+    use crate::EncodeAsType;
+    use alloc::vec;
+    use codec::Encode;
+    use scale_info::TypeInfo;
+
+    #[test]
+    fn logging_resolver_records_nested_resolutions() {
+        #[derive(TypeInfo, Encode)]
+        struct OuterTarget {
+            inner: InnerTarget,
+            flag: bool,
+        }
+        #[derive(TypeInfo, Encode)]
+        struct InnerTarget {
+            value: u32,
+        }
+
+        let (type_id, types) = make_type::<OuterTarget>();
+        let logging = LoggingResolver::new(&types);
+        let bytes = ((1u8,), true).encode_as_type(type_id, &logging).unwrap();
+        let target = OuterTarget {
+            inner: InnerTarget { value: 1 },
+            flag: true,
+        };
+        assert_eq!(bytes, target.encode());
+
+        let resolutions = logging.take_resolutions();
+        assert_eq!(resolutions[0].type_id, type_id);
+        // Types may be resolved more than once while encoding, so just check
+        // that each was seen, in order:
+        let mut kinds: Vec<_> = resolutions.iter().map(|r| r.kind).collect();
+        kinds.dedup();
+        assert_eq!(
+            kinds,
+            vec![
+                ResolvedKind::Composite,
+                ResolvedKind::Primitive(Primitive::U32),
+                ResolvedKind::Primitive(Primitive::Bool),
+            ]
+        );
+
+        // The log is cleared once taken:
+        assert!(logging.take_resolutions().is_empty());
+    }
+}
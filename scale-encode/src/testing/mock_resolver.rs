@@ -0,0 +1,257 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd. (admin@parity.io)
+// This file is a part of the scale-encode crate.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//         http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// A lightweight TypeResolver whose types are constructed by hand.
+
+use alloc::{
+    collections::BTreeMap,
+    string::{String, ToString},
+    vec::Vec,
+};
+use scale_type_resolver::{
+    BitsOrderFormat, BitsStoreFormat, Field, Primitive, ResolvedTypeVisitor, TypeResolver, Variant,
+};
+
+/// A [`TypeResolver`] whose types are added by hand, and which are
+/// identified by `u32` type IDs. This is useful for testing [`crate::EncodeAsType`]
+/// implementations without needing to construct a `scale_info::PortableRegistry`.
+///
+/// ```rust
+/// use scale_encode::{EncodeAsType, testing::MockResolver};
+/// use scale_type_resolver::Primitive;
+///
+/// let types = MockResolver::new()
+///     .add_primitive(0, Primitive::U32)
+///     .add_sequence(1, 0)
+///     .add_composite(2, [(Some("a"), 0), (Some("b"), 1)]);
+///
+/// let bytes = (123u8, vec![1u64, 2]).encode_as_type(2, &types).unwrap();
+/// assert_eq!(bytes, codec::Encode::encode(&(123u32, vec![1u32, 2])));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MockResolver {
+    types: BTreeMap<u32, MockType>,
+}
+
+#[derive(Debug, Clone)]
+enum MockType {
+    Primitive(Primitive),
+    Sequence(u32),
+    Array(u32, usize),
+    Tuple(Vec<u32>),
+    Compact(u32),
+    Composite(Vec<MockField>),
+    Variant(Vec<MockVariant>),
+    BitSequence(BitsStoreFormat, BitsOrderFormat),
+}
+
+#[derive(Debug, Clone)]
+struct MockField {
+    name: Option<String>,
+    id: u32,
+}
+
+#[derive(Debug, Clone)]
+struct MockVariant {
+    name: String,
+    index: u8,
+    fields: Vec<MockField>,
+}
+
+fn to_mock_fields<'a>(fields: impl IntoIterator<Item = (Option<&'a str>, u32)>) -> Vec<MockField> {
+    fields
+        .into_iter()
+        .map(|(name, id)| MockField {
+            name: name.map(ToString::to_string),
+            id,
+        })
+        .collect()
+}
+
+impl MockResolver {
+    /// Construct a new, empty resolver.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    fn add(mut self, id: u32, ty: MockType) -> Self {
+        self.types.insert(id, ty);
+        self
+    }
+
+    /// Add a primitive type with the given ID.
+    pub fn add_primitive(self, id: u32, primitive: Primitive) -> Self {
+        self.add(id, MockType::Primitive(primitive))
+    }
+
+    /// Add a sequence type with the given ID, whose elements have the type `elem_id`.
+    pub fn add_sequence(self, id: u32, elem_id: u32) -> Self {
+        self.add(id, MockType::Sequence(elem_id))
+    }
+
+    /// Add an array type with the given ID, containing `len` elements of the type `elem_id`.
+    pub fn add_array(self, id: u32, elem_id: u32, len: usize) -> Self {
+        self.add(id, MockType::Array(elem_id, len))
+    }
+
+    /// Add a tuple type with the given ID, whose entries have the types given.
+    pub fn add_tuple(self, id: u32, elem_ids: impl IntoIterator<Item = u32>) -> Self {
+        self.add(id, MockType::Tuple(elem_ids.into_iter().collect()))
+    }
+
+    /// Add a compact type with the given ID, wrapping the type `inner_id`.
+    pub fn add_compact(self, id: u32, inner_id: u32) -> Self {
+        self.add(id, MockType::Compact(inner_id))
+    }
+
+    /// Add a composite type with the given ID, whose fields have the optional names
+    /// and types given.
+    pub fn add_composite<'a>(
+        self,
+        id: u32,
+        fields: impl IntoIterator<Item = (Option<&'a str>, u32)>,
+    ) -> Self {
+        self.add(id, MockType::Composite(to_mock_fields(fields)))
+    }
+
+    /// Add a variant type with the given ID. Each variant is given as a name, an index
+    /// and a list of fields, which have optional names and types.
+    pub fn add_variant<'a>(
+        self,
+        id: u32,
+        variants: impl IntoIterator<Item = (&'a str, u8, Vec<(Option<&'a str>, u32)>)>,
+    ) -> Self {
+        let variants = variants
+            .into_iter()
+            .map(|(name, index, fields)| MockVariant {
+                name: name.to_string(),
+                index,
+                fields: to_mock_fields(fields),
+            })
+            .collect();
+        self.add(id, MockType::Variant(variants))
+    }
+
+    /// Add a bit sequence type with the given ID.
+    pub fn add_bit_sequence(
+        self,
+        id: u32,
+        store_format: BitsStoreFormat,
+        order_format: BitsOrderFormat,
+    ) -> Self {
+        self.add(id, MockType::BitSequence(store_format, order_format))
+    }
+}
+
+fn iter_fields(fields: &[MockField]) -> impl ExactSizeIterator<Item = Field<'_, u32>> {
+    fields.iter().map(|f| Field::new(f.id, f.name.as_deref()))
+}
+
+impl TypeResolver for MockResolver {
+    type TypeId = u32;
+    type Error = core::convert::Infallible;
+
+    fn resolve_type<'this, V: ResolvedTypeVisitor<'this, TypeId = Self::TypeId>>(
+        &'this self,
+        type_id: Self::TypeId,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        let Some(ty) = self.types.get(&type_id) else {
+            return Ok(visitor.visit_not_found());
+        };
+
+        let path = core::iter::empty();
+        let val = match ty {
+            MockType::Primitive(primitive) => visitor.visit_primitive(*primitive),
+            MockType::Sequence(elem_id) => visitor.visit_sequence(path, *elem_id),
+            MockType::Array(elem_id, len) => visitor.visit_array(*elem_id, *len),
+            MockType::Tuple(elem_ids) => visitor.visit_tuple(elem_ids.iter().copied()),
+            MockType::Compact(inner_id) => visitor.visit_compact(*inner_id),
+            MockType::Composite(fields) => visitor.visit_composite(path, iter_fields(fields)),
+            MockType::Variant(variants) => {
+                let variants = variants.iter().map(|v| Variant {
+                    index: v.index,
+                    name: &v.name,
+                    fields: iter_fields(&v.fields),
+                });
+                visitor.visit_variant(path, variants)
+            }
+            MockType::BitSequence(store, order) => visitor.visit_bit_sequence(*store, *order),
+        };
+
+        Ok(val)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{error::ErrorKind, EncodeAsType};
+    use alloc::{string::String, vec};
+    use codec::{Compact, Encode};
+
+    #[test]
+    fn can_encode_primitives_and_sequences() {
+        let types = MockResolver::new()
+            .add_primitive(0, Primitive::U16)
+            .add_sequence(1, 0)
+            .add_array(2, 0, 3)
+            .add_compact(3, 0);
+
+        assert_eq!(123u8.encode_as_type(0, &types).unwrap(), 123u16.encode());
+        assert_eq!(
+            vec![1u8, 2, 3].encode_as_type(1, &types).unwrap(),
+            vec![1u16, 2, 3].encode()
+        );
+        assert_eq!(
+            vec![1u8, 2, 3].encode_as_type(2, &types).unwrap(),
+            [1u16, 2, 3].encode()
+        );
+        assert_eq!(
+            1000u64.encode_as_type(3, &types).unwrap(),
+            Compact(1000u16).encode()
+        );
+    }
+
+    #[test]
+    fn can_encode_composites_tuples_and_variants() {
+        let types = MockResolver::new()
+            .add_primitive(0, Primitive::U8)
+            .add_primitive(1, Primitive::Str)
+            .add_composite(2, [(Some("a"), 0), (Some("b"), 1)])
+            .add_tuple(3, [1, 0])
+            .add_variant(4, [("None", 0, vec![]), ("Some", 5, vec![(None, 0)])]);
+
+        assert_eq!(
+            (1u64, "hi").encode_as_type(2, &types).unwrap(),
+            (1u8, "hi").encode()
+        );
+        assert_eq!(
+            (String::from("hi"), 1u64)
+                .encode_as_type(3, &types)
+                .unwrap(),
+            ("hi", 1u8).encode()
+        );
+        assert_eq!(Some(1u32).encode_as_type(4, &types).unwrap(), vec![5, 1]);
+        assert_eq!(None::<u32>.encode_as_type(4, &types).unwrap(), vec![0]);
+    }
+
+    #[test]
+    fn missing_types_are_not_found() {
+        let types = MockResolver::new();
+        let err = 1u8.encode_as_type(0, &types).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::TypeNotFound(_)));
+    }
+}
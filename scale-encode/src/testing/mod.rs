@@ -0,0 +1,104 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd. (admin@parity.io)
+// This file is a part of the scale-encode crate.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//         http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! This module provides some helpers for testing [`EncodeAsType`] implementations against
+//! types described by a [`scale_info::PortableRegistry`], as well as a [`MockResolver`] whose
+//! types are constructed by hand.
+//!
+//! ```rust
+//! use codec::{Decode, Encode};
+//! use scale_encode::{testing::assert_encodes_to, EncodeAsType, Error, TypeResolver};
+//! use scale_info::TypeInfo;
+//!
+//! // Some type with a custom EncodeAsType impl that we want to test:
+//! struct Celsius(f64);
+//!
+//! impl EncodeAsType for Celsius {
+//!     fn encode_as_type_to<R: TypeResolver>(
+//!         &self,
+//!         type_id: R::TypeId,
+//!         types: &R,
+//!         out: &mut Vec<u8>,
+//!     ) -> Result<(), Error> {
+//!         // Encode as hundredths of a degree:
+//!         let hundredths = (self.0 * 100.0).round() as i64;
+//!         hundredths.encode_as_type_to(type_id, types, out)
+//!     }
+//! }
+//!
+//! // Some type that we expect it to encode into:
+//! #[derive(Debug, PartialEq, Encode, Decode, TypeInfo)]
+//! struct Temperature {
+//!     centi_celsius: i32,
+//! }
+//!
+//! assert_encodes_to(Celsius(21.5), Temperature { centi_celsius: 2150 });
+//! assert_encodes_to(Celsius(-1.0), -100i16);
+//! ```
+
+mod mock_resolver;
+
+use crate::{EncodeAsType, Error};
+use alloc::vec::Vec;
+use codec::Decode;
+use core::fmt::Debug;
+use scale_info::{PortableRegistry, TypeInfo};
+
+pub use mock_resolver::MockResolver;
+
+/// Register the type `T` in a new [`PortableRegistry`], and return its type ID
+/// along with the registry.
+pub fn make_type<T: TypeInfo + 'static>() -> (u32, PortableRegistry) {
+    let m = scale_info::MetaType::new::<T>();
+    let mut types = scale_info::Registry::new();
+    let id = types.register_type(&m);
+    let portable_registry: PortableRegistry = types.into();
+
+    (id.id, portable_registry)
+}
+
+/// Encode some value into the shape of the type `T`.
+pub fn encode_type<V: EncodeAsType + ?Sized, T: TypeInfo + 'static>(
+    value: &V,
+) -> Result<Vec<u8>, Error> {
+    let (type_id, types) = make_type::<T>();
+    value.encode_as_type(type_id, &types)
+}
+
+/// Encode some value into the shape of the type of `expected`, and then decode the
+/// bytes back into that type, panicking if decoding fails, if any bytes are left over,
+/// or if the decoded value is not equal to `expected`.
+#[track_caller]
+pub fn assert_encodes_to<V, T>(value: V, expected: T)
+where
+    V: EncodeAsType,
+    T: PartialEq + Debug + Decode + TypeInfo + 'static,
+{
+    let bytes = match encode_type::<_, T>(&value) {
+        Ok(bytes) => bytes,
+        Err(e) => panic!("value could not be encoded: {e}"),
+    };
+    let cursor = &mut &*bytes;
+    let decoded = match T::decode(cursor) {
+        Ok(decoded) => decoded,
+        Err(e) => panic!("encoded bytes could not be decoded: {e}"),
+    };
+
+    assert_eq!(cursor.len(), 0, "no bytes should be remaining");
+    assert_eq!(
+        expected, decoded,
+        "value does not roundtrip and decode to expected value"
+    );
+}
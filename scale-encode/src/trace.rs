@@ -0,0 +1,259 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd. (admin@parity.io)
+// This file is a part of the scale-encode crate.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//         http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! This module provides a [`TraceEvent`] type, which describes a step taken while
+//! encoding some value. Use [`crate::EncodeAsType::encode_as_type_traced()`] to
+//! obtain a trace of these events, which can help to debug why some value was
+//! encoded in an unexpected way, or failed to encode.
+
+use alloc::{format, string::String, vec::Vec};
+use core::cell::RefCell;
+use scale_type_resolver::{
+    BitsOrderFormat, BitsStoreFormat, FieldIter, PathIter, Primitive, ResolvedTypeVisitor,
+    UnhandledKind, VariantIter,
+};
+
+std::thread_local! {
+    static EVENTS: RefCell<Option<Vec<TraceEvent>>> = const { RefCell::new(None) };
+}
+
+/// A single step taken while encoding some value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TraceEvent {
+    /// A type was resolved via the [`crate::TypeResolver`], and this is the shape it had.
+    Resolved {
+        /// Identifier of the type that was resolved.
+        type_id: String,
+        /// A description of the shape that the type resolved to.
+        shape: String,
+    },
+    /// Some decision was taken while encoding, for instance to widen a number
+    /// or to line fields up by name.
+    Decision(String),
+}
+
+impl core::fmt::Display for TraceEvent {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            TraceEvent::Resolved { type_id, shape } => {
+                write!(f, "resolved type {type_id} to {shape}")
+            }
+            TraceEvent::Decision(decision) => f.write_str(decision),
+        }
+    }
+}
+
+// Run the closure, collecting and returning any events recorded while it runs.
+pub(crate) fn collect<T>(f: impl FnOnce() -> T) -> (T, Vec<TraceEvent>) {
+    // Put back whatever was being collected before once we're done, even if `f` panics,
+    // so that we don't carry on collecting events that nobody will take.
+    struct RestoreOnDrop(Option<Vec<TraceEvent>>);
+    impl Drop for RestoreOnDrop {
+        fn drop(&mut self) {
+            let prev = self.0.take();
+            EVENTS.with(|events| *events.borrow_mut() = prev);
+        }
+    }
+
+    let _restore = RestoreOnDrop(EVENTS.with(|events| events.borrow_mut().replace(Vec::new())));
+    let res = f();
+    let events = EVENTS.with(|events| events.borrow_mut().take());
+    (res, events.unwrap_or_default())
+}
+
+// Record an event, if we are currently collecting them.
+pub(crate) fn record(event: impl FnOnce() -> TraceEvent) {
+    EVENTS.with(|events| {
+        if let Some(events) = &mut *events.borrow_mut() {
+            events.push(event());
+        }
+    });
+}
+
+// Describe a number conversion, eg "widen u8 to u64".
+pub(crate) fn number_decision<From, To>() -> TraceEvent {
+    let from = core::any::type_name::<From>();
+    let to = core::any::type_name::<To>();
+    let from_size = core::mem::size_of::<From>();
+    let to_size = core::mem::size_of::<To>();
+
+    let decision = if from == to {
+        format!("encode {from} as {to}")
+    } else if from_size < to_size {
+        format!("widen {from} to {to}")
+    } else if from_size > to_size {
+        format!("narrow {from} to {to}")
+    } else {
+        format!("convert {from} to {to}")
+    };
+    TraceEvent::Decision(decision)
+}
+
+/// A [`ResolvedTypeVisitor`] which records the shape that a type resolves to
+/// before handing over to the visitor that it wraps. The type ID is only formatted
+/// if events are being collected.
+pub(crate) struct TracingVisitor<TypeId, V> {
+    pub type_id: TypeId,
+    pub inner: V,
+}
+
+impl<TypeId: core::fmt::Debug, V> TracingVisitor<TypeId, V> {
+    fn record(&self, shape: impl FnOnce() -> String) {
+        record(|| TraceEvent::Resolved {
+            type_id: format!("{:?}", self.type_id),
+            shape: shape(),
+        })
+    }
+}
+
+impl<'resolver, V: ResolvedTypeVisitor<'resolver>> ResolvedTypeVisitor<'resolver>
+    for TracingVisitor<V::TypeId, V>
+{
+    type TypeId = V::TypeId;
+    type Value = V::Value;
+
+    fn visit_unhandled(self, kind: UnhandledKind) -> Self::Value {
+        self.inner.visit_unhandled(kind)
+    }
+    fn visit_not_found(self) -> Self::Value {
+        self.record(|| String::from("nothing; type not found"));
+        self.inner.visit_not_found()
+    }
+    fn visit_composite<Path, Fields>(self, path: Path, fields: Fields) -> Self::Value
+    where
+        Path: PathIter<'resolver>,
+        Fields: FieldIter<'resolver, Self::TypeId>,
+    {
+        self.record(|| format!("a composite with {} fields", fields.len()));
+        self.inner.visit_composite(path, fields)
+    }
+    fn visit_variant<Path, Fields, Var>(self, path: Path, variants: Var) -> Self::Value
+    where
+        Path: PathIter<'resolver>,
+        Fields: FieldIter<'resolver, Self::TypeId>,
+        Var: VariantIter<'resolver, Fields>,
+    {
+        self.record(|| format!("a variant with {} variants", variants.len()));
+        self.inner.visit_variant(path, variants)
+    }
+    fn visit_sequence<Path>(self, path: Path, type_id: Self::TypeId) -> Self::Value
+    where
+        Path: PathIter<'resolver>,
+    {
+        self.record(|| format!("a sequence of type {type_id:?}"));
+        self.inner.visit_sequence(path, type_id)
+    }
+    fn visit_array(self, type_id: Self::TypeId, len: usize) -> Self::Value {
+        self.record(|| format!("an array of {len} values of type {type_id:?}"));
+        self.inner.visit_array(type_id, len)
+    }
+    fn visit_tuple<TypeIds>(self, type_ids: TypeIds) -> Self::Value
+    where
+        TypeIds: ExactSizeIterator<Item = Self::TypeId>,
+    {
+        self.record(|| format!("a tuple with {} entries", type_ids.len()));
+        self.inner.visit_tuple(type_ids)
+    }
+    fn visit_primitive(self, primitive: Primitive) -> Self::Value {
+        self.record(|| format!("the primitive {primitive:?}"));
+        self.inner.visit_primitive(primitive)
+    }
+    fn visit_compact(self, type_id: Self::TypeId) -> Self::Value {
+        self.record(|| format!("a compact value of type {type_id:?}"));
+        self.inner.visit_compact(type_id)
+    }
+    fn visit_bit_sequence(
+        self,
+        store_format: BitsStoreFormat,
+        order_format: BitsOrderFormat,
+    ) -> Self::Value {
+        self.record(|| format!("a bit sequence ({store_format:?}, {order_format:?})"));
+        self.inner.visit_bit_sequence(store_format, order_format)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::make_type;
+    use crate::EncodeAsType;
+    use alloc::string::ToString;
+
+    #[test]
+    fn trace_records_number_widening() {
+        let (type_id, types) = make_type::<u64>();
+        let (res, events) = 123u8.encode_as_type_traced(type_id, &types);
+
+        assert_eq!(res.unwrap(), codec::Encode::encode(&123u64));
+        assert_eq!(
+            events,
+            [
+                TraceEvent::Resolved {
+                    type_id: type_id.to_string(),
+                    shape: "the primitive U64".to_string()
+                },
+                TraceEvent::Decision("widen u8 to u64".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn trace_records_sequence_elements() {
+        let (type_id, types) = make_type::<Vec<u32>>();
+        let (res, events) = [1u8, 2].encode_as_type_traced(type_id, &types);
+
+        res.unwrap();
+        let events: Vec<_> = events.iter().map(|e| e.to_string()).collect();
+        assert!(events[0].contains("a sequence of type"));
+        assert_eq!(events[1], "encode 2 values as a sequence");
+        assert!(events.iter().any(|e| e == "widen u8 to u32"));
+    }
+
+    #[test]
+    fn traces_are_not_collected_by_default() {
+        // Events aren't even built unless they are being collected:
+        record(|| panic!("events should only be built while collecting them"));
+
+        // Encoding normally doesn't store any events:
+        let (type_id, types) = make_type::<u64>();
+        123u8.encode_as_type(type_id, &types).unwrap();
+        assert!(EVENTS.with(|events| events.borrow().is_none()));
+
+        // And once a trace has been collected, we go back to not storing them:
+        let (res, events) = 123u8.encode_as_type_traced(type_id, &types);
+        res.unwrap();
+        assert!(!events.is_empty());
+        assert!(EVENTS.with(|events| events.borrow().is_none()));
+    }
+
+    #[test]
+    fn traces_stop_being_collected_if_encoding_panics() {
+        let res = std::panic::catch_unwind(|| {
+            collect(|| {
+                record(|| TraceEvent::Decision("about to panic".to_string()));
+                panic!("encoding panicked");
+            })
+        });
+        assert!(res.is_err());
+        assert!(EVENTS.with(|events| events.borrow().is_none()));
+
+        // Nested collections are also restored to what they were:
+        let (_, outer) = collect(|| {
+            let _ = std::panic::catch_unwind(|| collect(|| panic!("encoding panicked")));
+            record(|| TraceEvent::Decision("after the panic".to_string()));
+        });
+        assert_eq!(outer, [TraceEvent::Decision("after the panic".to_string())]);
+    }
+}
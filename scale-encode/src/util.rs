@@ -0,0 +1,351 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd. (admin@parity.io)
+// This file is a part of the scale-encode crate.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//         http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! This module provides some helper functions for encoding lists of values of
+//! different types, such as the arguments to some call, without first needing to
+//! gather them into a tuple or struct.
+
+use crate::{error::collect, Composite, CompositeField, DynEncodeAsType, Error, Variant};
+use alloc::vec::Vec;
+use scale_type_resolver::TypeResolver;
+
+/// Encode a list of values, each of which can be a different type, into the tuple
+/// or composite type given by `type_id`. Values are lined up with the fields of the
+/// target type by position.
+///
+/// ```rust
+/// use codec::Encode;
+/// use scale_encode::util::encode_nested;
+/// use scale_info::PortableRegistry;
+///
+/// let m = scale_info::MetaType::new::<(u32, bool, String)>();
+/// let mut types = scale_info::Registry::new();
+/// let type_id = types.register_type(&m).id;
+/// let types: PortableRegistry = types.into();
+///
+/// let bytes = encode_nested(type_id, &types, &[&1u8, &true, &"hello"]).unwrap();
+/// assert_eq!(bytes, (1u32, true, "hello").encode());
+/// ```
+pub fn encode_nested<R: TypeResolver>(
+    type_id: R::TypeId,
+    types: &R,
+    values: &[&dyn DynEncodeAsType<R>],
+) -> Result<Vec<u8>, Error> {
+    let mut out = Vec::new();
+    values.encode_as_type_with_resolver_to(type_id, types, &mut out)?;
+    Ok(out)
+}
+
+/// Encode a list of values, each of which can be a different type, into the fields of
+/// the variant called `variant_name` in the enum type given by `type_id`. Values are lined
+/// up with the fields of the variant by position.
+///
+/// This is useful for encoding the arguments to a call, for instance:
+///
+/// ```rust
+/// use codec::Encode;
+/// use scale_encode::util::encode_nested_variant;
+/// use scale_info::{PortableRegistry, TypeInfo};
+///
+/// #[allow(non_camel_case_types)]
+/// #[derive(Encode, TypeInfo)]
+/// enum BalancesCall {
+///     transfer { dest: [u8; 4], value: u128 },
+/// }
+///
+/// let m = scale_info::MetaType::new::<BalancesCall>();
+/// let mut types = scale_info::Registry::new();
+/// let type_id = types.register_type(&m).id;
+/// let types: PortableRegistry = types.into();
+///
+/// // Calls are prefixed with the index of the pallet that they belong to:
+/// let pallet_index = 5u8;
+/// let dest = vec![1u8, 2, 3, 4];
+/// let value = 1000u64;
+///
+/// let mut call = vec![pallet_index];
+/// call.extend(encode_nested_variant(type_id, &types, "transfer", &[&dest, &value]).unwrap());
+///
+/// let expected = (pallet_index, BalancesCall::transfer { dest: [1, 2, 3, 4], value: 1000 });
+/// assert_eq!(call, expected.encode());
+/// ```
+pub fn encode_nested_variant<R: TypeResolver>(
+    type_id: R::TypeId,
+    types: &R,
+    variant_name: &str,
+    values: &[&dyn DynEncodeAsType<R>],
+) -> Result<Vec<u8>, Error> {
+    let fields = values.iter().map(|v| (None, CompositeField::new_dyn(*v)));
+    Variant {
+        name: variant_name,
+        fields: Composite::new(fields),
+    }
+    .encode_variant_as_type(type_id, types)
+}
+
+/// Encode a value into each of the types given by `type_ids` in turn, returning the output
+/// from the first one that succeeds, or else the error from the last one that was tried. This
+/// is useful when the type that a value should be encoded into may have different IDs (or
+/// shapes), for instance across different versions of some metadata.
+///
+/// An error is returned if no type IDs are given.
+///
+/// ```rust
+/// use codec::Encode;
+/// use scale_encode::util::encode_as_type_or;
+/// use scale_info::PortableRegistry;
+///
+/// let m = scale_info::MetaType::new::<(bool, u64)>();
+/// let mut types = scale_info::Registry::new();
+/// let type_id = types.register_type(&m).id;
+/// let types: PortableRegistry = types.into();
+///
+/// // Find the type IDs for the bool and u64 in our registry.
+/// let scale_info::TypeDef::Tuple(t) = &types.resolve(type_id).unwrap().type_def else {
+///     panic!("expected a tuple")
+/// };
+/// let (bool_id, u64_id) = (t.fields[0].id, t.fields[1].id);
+///
+/// // 123u8 can't be encoded into a bool, so it's encoded into the u64 instead:
+/// let bytes = encode_as_type_or(&123u8, &[bool_id, u64_id], &types).unwrap();
+/// assert_eq!(bytes, 123u64.encode());
+/// ```
+pub fn encode_as_type_or<T: crate::EncodeAsType + ?Sized, R: TypeResolver>(
+    value: &T,
+    type_ids: &[R::TypeId],
+    types: &R,
+) -> Result<Vec<u8>, Error> {
+    let mut last_err = None;
+    for type_id in type_ids {
+        // We rely on errors to know whether each attempt worked, so don't collect them.
+        match collect::suspended(|| value.encode_as_type(type_id.clone(), types)) {
+            Ok(bytes) => return Ok(bytes),
+            Err(e) => last_err = Some(e),
+        }
+    }
+    Err(last_err.unwrap_or_else(|| Error::custom_str("No type IDs were given to encode into")))
+}
+
+/// Encode a value into the type in the given [`scale_info::PortableRegistry`] whose path
+/// is `path`, for instance `"pallet_balances::pallet::Call"`. An error is returned if there
+/// is not exactly one type with this path; generic types may appear more than once in a
+/// registry with different type parameters, and so can't be identified by their path alone.
+///
+/// ```rust
+/// use codec::Encode;
+/// use scale_encode::util::encode_as_type_by_path;
+/// use scale_info::{PortableRegistry, TypeInfo};
+///
+/// mod pallet {
+///     #[derive(codec::Encode, scale_info::TypeInfo)]
+///     pub struct Foo {
+///         pub a: u64,
+///     }
+/// }
+///
+/// let m = scale_info::MetaType::new::<pallet::Foo>();
+/// let mut types = scale_info::Registry::new();
+/// types.register_type(&m);
+/// let types: PortableRegistry = types.into();
+///
+/// let path = concat!(module_path!(), "::pallet::Foo");
+/// let bytes = encode_as_type_by_path(&(123u8,), path, &types).unwrap();
+/// assert_eq!(bytes, pallet::Foo { a: 123 }.encode());
+/// ```
+#[cfg(feature = "scale-info")]
+pub fn encode_as_type_by_path<T: crate::EncodeAsType + ?Sized>(
+    value: &T,
+    path: &str,
+    types: &scale_info::PortableRegistry,
+) -> Result<Vec<u8>, Error> {
+    let mut matching_ids = types.types.iter().filter_map(|ty| {
+        let segments = &ty.ty.path.segments;
+        let is_match = !segments.is_empty()
+            && path
+                .split("::")
+                .eq(segments.iter().map(AsRef::<str>::as_ref));
+        is_match.then_some(ty.id)
+    });
+
+    let Some(type_id) = matching_ids.next() else {
+        return Err(Error::custom_string(alloc::format!(
+            "Cannot find a type with the path {path}"
+        )));
+    };
+    if matching_ids.next().is_some() {
+        return Err(Error::custom_string(alloc::format!(
+            "More than one type has the path {path}, so it can't be used to identify a type"
+        )));
+    }
+
+    value.encode_as_type(type_id, types)
+}
+
+/// Decode the SCALE encoded `frame-metadata` bytes given (ie a `RuntimeMetadataPrefixed`), and
+/// then encode a value into the type given by `type_id` in its type registry. This is useful for
+/// tooling which has raw metadata bytes to hand rather than a decoded
+/// [`scale_info::PortableRegistry`]. V14 and V15 metadata are supported.
+///
+/// ```rust
+/// use codec::Encode;
+/// use frame_metadata::{
+///     v14::{ExtrinsicMetadata, RuntimeMetadataV14},
+///     RuntimeMetadata, RuntimeMetadataPrefixed, META_RESERVED,
+/// };
+/// use scale_encode::util::encode_as_type_from_metadata_bytes;
+///
+/// let extrinsic = ExtrinsicMetadata {
+///     ty: scale_info::MetaType::new::<()>(),
+///     version: 4,
+///     signed_extensions: vec![],
+/// };
+/// let metadata = RuntimeMetadataV14::new(vec![], extrinsic, scale_info::MetaType::new::<(u32, bool)>());
+/// let type_id = metadata.ty.id;
+/// let metadata_bytes = RuntimeMetadataPrefixed(META_RESERVED, RuntimeMetadata::V14(metadata)).encode();
+///
+/// let bytes = encode_as_type_from_metadata_bytes(&(1u8, true), type_id, &metadata_bytes).unwrap();
+/// assert_eq!(bytes, (1u32, true).encode());
+/// ```
+#[cfg(feature = "frame-metadata")]
+pub fn encode_as_type_from_metadata_bytes<T: crate::EncodeAsType + ?Sized>(
+    value: &T,
+    type_id: u32,
+    mut metadata_bytes: &[u8],
+) -> Result<Vec<u8>, Error> {
+    use codec::Decode;
+    use frame_metadata::{RuntimeMetadata, RuntimeMetadataPrefixed};
+
+    let metadata = RuntimeMetadataPrefixed::decode(&mut metadata_bytes)
+        .map_err(|e| Error::custom_string(alloc::format!("Cannot decode the metadata: {e}")))?;
+    let types = match &metadata.1 {
+        RuntimeMetadata::V14(m) => &m.types,
+        RuntimeMetadata::V15(m) => &m.types,
+        m => {
+            return Err(Error::custom_string(alloc::format!(
+                "Cannot use V{} metadata; only V14 and V15 metadata are supported",
+                m.version()
+            )))
+        }
+    };
+    value.encode_as_type(type_id, types)
+}
+
+#[cfg(all(test, feature = "scale-info"))]
+mod test {
+    use super::*;
+    use alloc::string::ToString;
+    use codec::Encode;
+    use scale_info::{PortableRegistry, TypeInfo};
+
+    #[derive(Encode, TypeInfo)]
+    struct Foo {
+        a: u64,
+    }
+
+    #[derive(Encode, TypeInfo)]
+    struct Wrapper<T>(T);
+
+    fn make_types<T: TypeInfo + 'static>() -> PortableRegistry {
+        let m = scale_info::MetaType::new::<T>();
+        let mut types = scale_info::Registry::new();
+        types.register_type(&m);
+        types.into()
+    }
+
+    #[test]
+    fn encodes_into_type_with_unique_path() {
+        let types = make_types::<(Foo, bool)>();
+        let path = concat!(module_path!(), "::Foo");
+        let bytes = encode_as_type_by_path(&(1u8,), path, &types).unwrap();
+        assert_eq!(bytes, Foo { a: 1 }.encode());
+    }
+
+    #[test]
+    fn errors_if_no_type_has_path() {
+        let types = make_types::<(Foo, bool)>();
+        let err = encode_as_type_by_path(&(1u8,), "not::Foo", &types).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Cannot find a type with the path not::Foo"));
+    }
+
+    #[test]
+    fn errors_if_several_types_have_path() {
+        let types = make_types::<(Wrapper<u8>, Wrapper<bool>)>();
+        let path = concat!(module_path!(), "::Wrapper");
+        let err = encode_as_type_by_path(&1u8, path, &types).unwrap_err();
+        assert!(err.to_string().contains("More than one type has the path"));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn encode_as_type_or_works_when_collecting_errors() {
+        let m = scale_info::MetaType::new::<(bool, u64)>();
+        let mut types = scale_info::Registry::new();
+        let type_id = types.register_type(&m).id;
+        let types: PortableRegistry = types.into();
+        let scale_info::TypeDef::Tuple(t) = &types.resolve(type_id).unwrap().type_def else {
+            panic!("expected a tuple")
+        };
+        let (bool_id, u64_id) = (t.fields[0].id, t.fields[1].id);
+
+        // The failed attempt to encode into a bool isn't collected:
+        let (res, errors) =
+            collect::collect(|| encode_as_type_or(&123u8, &[bool_id, u64_id], &types));
+        assert_eq!(res.unwrap(), 123u64.encode());
+        assert!(errors.is_empty());
+    }
+
+    #[cfg(feature = "frame-metadata")]
+    #[test]
+    fn encodes_using_types_from_metadata_bytes() {
+        use frame_metadata::{
+            v14::{ExtrinsicMetadata, RuntimeMetadataV14},
+            OpaqueMetadata, RuntimeMetadata, RuntimeMetadataPrefixed, META_RESERVED,
+        };
+
+        let extrinsic = ExtrinsicMetadata {
+            ty: scale_info::MetaType::new::<()>(),
+            version: 4,
+            signed_extensions: alloc::vec![],
+        };
+        let metadata = RuntimeMetadataV14::new(
+            alloc::vec![],
+            extrinsic,
+            scale_info::MetaType::new::<Wrapper<Foo>>(),
+        );
+        let type_id = metadata.ty.id;
+        let metadata_bytes =
+            RuntimeMetadataPrefixed(META_RESERVED, RuntimeMetadata::V14(metadata)).encode();
+
+        let bytes =
+            encode_as_type_from_metadata_bytes(&((1u8,),), type_id, &metadata_bytes).unwrap();
+        assert_eq!(bytes, Wrapper(Foo { a: 1 }).encode());
+
+        let err =
+            encode_as_type_from_metadata_bytes(&1u8, type_id, &metadata_bytes[..7]).unwrap_err();
+        assert!(err.to_string().contains("Cannot decode the metadata"));
+
+        // Older metadata doesn't contain a type registry:
+        let old_metadata_bytes = RuntimeMetadataPrefixed(
+            META_RESERVED,
+            RuntimeMetadata::V13(OpaqueMetadata(alloc::vec![])),
+        )
+        .encode();
+        let err =
+            encode_as_type_from_metadata_bytes(&1u8, type_id, &old_metadata_bytes).unwrap_err();
+        assert!(err.to_string().contains("Cannot use V13 metadata"));
+    }
+}
@@ -0,0 +1,46 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd. (admin@parity.io)
+// This file is a part of the scale-encode crate.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//         http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use scale_encode::EncodeAsType;
+use std::marker::PhantomData;
+
+// Doesn't implement EncodeAsType:
+struct NotEncodable;
+
+#[derive(EncodeAsType)]
+struct Foo<T, U, V> {
+    a: u8,
+    // These fields don't need T or U to implement EncodeAsType:
+    #[encode_as_type(bound = "")]
+    marker: PhantomData<T>,
+    #[encode_as_type(skip, bound = "")]
+    skipped: U,
+    // We can give our own bounds for a field too:
+    #[encode_as_type(bound = "V: Clone + EncodeAsType")]
+    value: V,
+}
+
+#[derive(EncodeAsType)]
+enum Bar<T, U> {
+    A(#[encode_as_type(bound = "")] PhantomData<T>),
+    B(U),
+}
+
+fn can_encode_as_type<T: EncodeAsType>() {}
+
+fn main() {
+    can_encode_as_type::<Foo<NotEncodable, NotEncodable, u8>>();
+    can_encode_as_type::<Bar<NotEncodable, u8>>();
+}
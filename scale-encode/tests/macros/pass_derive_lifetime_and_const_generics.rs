@@ -0,0 +1,40 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd. (admin@parity.io)
+// This file is a part of the scale-encode crate.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//         http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+
+use scale_encode::EncodeAsType;
+
+#[derive(EncodeAsType)]
+struct Foo<'a, T, const N: usize> {
+    data: &'a [T; N],
+}
+
+#[derive(EncodeAsType)]
+struct Bar<'a, T, const N: usize>(&'a [T; N]);
+
+#[derive(EncodeAsType)]
+enum Wibble<'a, 'b, T, const N: usize> {
+    Foo(Foo<'a, T, N>),
+    Bar { bar: Bar<'b, T, N> },
+}
+
+fn can_encode_as_type<T: EncodeAsType>() {}
+
+fn main() {
+    // assert that the trait is implemented as expected:
+    can_encode_as_type::<Foo<'static, u8, 16>>();
+    can_encode_as_type::<Bar<'static, String, 0>>();
+    can_encode_as_type::<Wibble<'static, 'static, bool, 3>>();
+}
@@ -0,0 +1,42 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd. (admin@parity.io)
+// This file is a part of the scale-encode crate.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//         http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use scale_encode::{EncodeAsFields, EncodeAsType, Error, FieldIter, TypeResolver};
+
+// We opt out of the derived EncodeAsFields impl:
+#[derive(EncodeAsType)]
+#[encode_as_type(no_fields)]
+struct Foo {
+    some_field: u8,
+}
+
+// ..so that we can write our own without them conflicting:
+impl EncodeAsFields for Foo {
+    fn encode_as_fields_to<R: TypeResolver>(
+        &self,
+        _fields: &mut dyn FieldIter<'_, R::TypeId>,
+        _types: &R,
+        out: &mut Vec<u8>,
+    ) -> Result<(), Error> {
+        out.push(self.some_field);
+        Ok(())
+    }
+}
+
+fn can_encode_as_type_and_fields<T: EncodeAsType + EncodeAsFields>() {}
+
+fn main() {
+    can_encode_as_type_and_fields::<Foo>();
+}
@@ -0,0 +1,97 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd. (admin@parity.io)
+// This file is a part of the scale-encode crate.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//         http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use codec::Encode;
+use core::marker::PhantomData;
+use scale_encode::EncodeAsType;
+use scale_info::{PortableRegistry, TypeInfo};
+
+// Doesn't implement EncodeAsType:
+struct NotEncodeAsType;
+
+// PhantomData fields are skipped, and don't require T: EncodeAsType.
+#[derive(EncodeAsType)]
+struct Foo<T> {
+    a: u64,
+    _marker: PhantomData<T>,
+}
+
+#[derive(EncodeAsType)]
+struct Bar<T>(u64, PhantomData<T>);
+
+#[derive(EncodeAsType)]
+enum Wibble<T> {
+    A(bool, core::marker::PhantomData<T>),
+}
+
+// PhantomData fields can be kept with `skip = false`, and are then encoded like ().
+#[derive(EncodeAsType)]
+struct Kept<T> {
+    a: u64,
+    #[encode_as_type(skip = false)]
+    _marker: PhantomData<T>,
+}
+
+// Type information also leaves out PhantomData fields.
+#[derive(Encode, TypeInfo)]
+struct Target {
+    a: u64,
+}
+
+#[derive(Encode, TypeInfo)]
+struct KeptTarget {
+    a: u64,
+    _marker: (),
+}
+
+fn make_type<T: TypeInfo + 'static>() -> (u32, PortableRegistry) {
+    let m = scale_info::MetaType::new::<T>();
+    let mut types = scale_info::Registry::new();
+    let type_id = types.register_type(&m).id;
+    (type_id, types.into())
+}
+
+fn can_encode_as_type<T: EncodeAsType>() {}
+
+fn main() {
+    // assert that the trait is implemented:
+    can_encode_as_type::<Foo<NotEncodeAsType>>();
+    can_encode_as_type::<Bar<NotEncodeAsType>>();
+    can_encode_as_type::<Wibble<NotEncodeAsType>>();
+    can_encode_as_type::<Kept<NotEncodeAsType>>();
+
+    let (type_id, types) = make_type::<Target>();
+    let foo = Foo::<NotEncodeAsType> {
+        a: 123,
+        _marker: PhantomData,
+    };
+    let encoded = foo.encode_as_type(type_id, &types).unwrap();
+    assert_eq!(encoded, Target { a: 123 }.encode());
+
+    let kept = Kept::<NotEncodeAsType> {
+        a: 123,
+        _marker: PhantomData,
+    };
+    let (type_id, types) = make_type::<KeptTarget>();
+    let encoded = kept.encode_as_type(type_id, &types).unwrap();
+    assert_eq!(
+        encoded,
+        KeptTarget {
+            a: 123,
+            _marker: ()
+        }
+        .encode()
+    );
+}
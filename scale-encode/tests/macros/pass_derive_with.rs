@@ -0,0 +1,97 @@
+// Copyright (C) 2023 Parity Technologies (UK) Ltd. (admin@parity.io)
+// This file is a part of the scale-encode crate.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//         http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use codec::Encode;
+use scale_encode::{EncodeAsType, Error, TypeResolver};
+use scale_info::{PortableRegistry, TypeInfo};
+
+// Doesn't implement EncodeAsType:
+struct Celsius(f64);
+
+mod celsius {
+    use super::*;
+
+    // Encode the temperature as a whole number of degrees.
+    pub fn encode<R: TypeResolver>(
+        value: &Celsius,
+        type_id: R::TypeId,
+        types: &R,
+        out: &mut Vec<u8>,
+    ) -> Result<(), Error> {
+        (value.0.round() as i32).encode_as_type_to(type_id, types, out)
+    }
+}
+
+#[derive(EncodeAsType)]
+struct Reading {
+    #[encode_as_type(with = "celsius::encode")]
+    temperature: Celsius,
+    #[encode_as_type(with = "celsius::encode")]
+    max: Celsius,
+    // Codec attributes don't stop `with` from being used:
+    #[codec(compact)]
+    #[encode_as_type(with = "celsius::encode")]
+    min: Celsius,
+    station: String,
+}
+
+#[derive(EncodeAsType)]
+enum Event {
+    Reading(#[encode_as_type(with = "celsius::encode")] Celsius, u8),
+}
+
+#[derive(Encode, TypeInfo)]
+struct TargetReading {
+    station: String,
+    temperature: i32,
+    max: i32,
+    min: i32,
+}
+
+#[derive(Encode, TypeInfo)]
+enum TargetEvent {
+    Reading(i32, u8),
+}
+
+fn make_type<T: TypeInfo + 'static>() -> (u32, PortableRegistry) {
+    let m = scale_info::MetaType::new::<T>();
+    let mut types = scale_info::Registry::new();
+    let id = types.register_type(&m);
+    (id.id, types.into())
+}
+
+fn main() {
+    let reading = Reading {
+        temperature: Celsius(21.4),
+        max: Celsius(-3.6),
+        min: Celsius(-10.1),
+        station: "north".to_string(),
+    };
+    let (type_id, types) = make_type::<TargetReading>();
+    let encoded = reading.encode_as_type(type_id, &types).unwrap();
+    let expected = TargetReading {
+        station: "north".to_string(),
+        temperature: 21,
+        max: -4,
+        min: -10,
+    };
+    assert_eq!(encoded, expected.encode());
+
+    let (type_id, types) = make_type::<TargetEvent>();
+    let encoded = Event::Reading(Celsius(9.9), 2)
+        .encode_as_type(type_id, &types)
+        .unwrap();
+    assert_eq!(encoded, TargetEvent::Reading(10, 2).encode());
+}